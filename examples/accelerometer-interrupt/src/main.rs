@@ -0,0 +1,90 @@
+#![no_std]
+#![no_main]
+
+use defmt_rtt as _;
+use panic_halt as _;
+
+use core::cell::RefCell;
+
+use cortex_m::interrupt::Mutex;
+use cortex_m_rt::entry;
+use microbit::{
+    hal::gpiote::Gpiote,
+    motion::{Accelerometer, Interrupt, InterruptAccelerometer},
+    pac::{self, interrupt},
+};
+
+#[cfg(feature = "v1")]
+use microbit::{
+    hal::twi::Twi as I2c,
+    pac::{twi0::frequency::FREQUENCY_A, TWI0 as I2C},
+};
+#[cfg(feature = "v2")]
+use microbit::{
+    hal::twim::Twim as I2c,
+    pac::{twim0::frequency::FREQUENCY_A, TWIM0 as I2C},
+};
+
+static GPIOTE: Mutex<RefCell<Option<Gpiote>>> = Mutex::new(RefCell::new(None));
+static ACCELEROMETER: Mutex<RefCell<Option<InterruptAccelerometer<I2c<I2C>>>>> =
+    Mutex::new(RefCell::new(None));
+
+#[entry]
+fn main() -> ! {
+    let board = microbit::Board::take().unwrap();
+
+    #[cfg(feature = "v1")]
+    let i2c = { I2c::new(board.TWI0, board.i2c.into(), FREQUENCY_A::K100) };
+
+    #[cfg(feature = "v2")]
+    let i2c = { I2c::new(board.TWIM0, board.i2c_internal.into(), FREQUENCY_A::K100) };
+
+    let accelerometer = match Accelerometer::new(i2c) {
+        Ok(accelerometer) => accelerometer,
+        Err(_) => defmt::panic!("No accelerometer found"),
+    };
+
+    // the sensor pulls INT_ACC low when a sample is ready
+    let gpiote = Gpiote::new(board.GPIOTE);
+    let accelerometer = match InterruptAccelerometer::new(
+        accelerometer,
+        board.int_acc,
+        &gpiote.channel0(),
+        Interrupt::DataReady,
+    ) {
+        Ok(accelerometer) => accelerometer,
+        Err(_) => defmt::panic!("Could not enable the accelerometer interrupt"),
+    };
+
+    cortex_m::interrupt::free(move |cs| {
+        *GPIOTE.borrow(cs).borrow_mut() = Some(gpiote);
+        *ACCELEROMETER.borrow(cs).borrow_mut() = Some(accelerometer);
+    });
+    unsafe {
+        pac::NVIC::unmask(pac::Interrupt::GPIOTE);
+    }
+
+    loop {
+        // sleep until the next sample
+        cortex_m::asm::wfi();
+    }
+}
+
+#[interrupt]
+fn GPIOTE() {
+    cortex_m::interrupt::free(|cs| {
+        if let (Some(gpiote), Some(accelerometer)) = (
+            GPIOTE.borrow(cs).borrow().as_ref(),
+            ACCELEROMETER.borrow(cs).borrow_mut().as_mut(),
+        ) {
+            if let Ok(Some(acceleration)) = accelerometer.handle_interrupt(&gpiote.channel0()) {
+                defmt::info!(
+                    "x {} y {} z {} mg",
+                    acceleration.x,
+                    acceleration.y,
+                    acceleration.z
+                );
+            }
+        }
+    });
+}
@@ -0,0 +1,46 @@
+#![no_std]
+#![no_main]
+
+use defmt_rtt as _;
+use panic_halt as _;
+
+use cortex_m_rt::entry;
+use embedded_hal::delay::DelayNs;
+use microbit::{hal::Timer, motion::Accelerometer};
+
+#[cfg(feature = "v1")]
+use microbit::{hal::twi, pac::twi0::frequency::FREQUENCY_A};
+#[cfg(feature = "v2")]
+use microbit::{hal::twim, pac::twim0::frequency::FREQUENCY_A};
+
+#[entry]
+fn main() -> ! {
+    let board = microbit::Board::take().unwrap();
+    let mut timer = Timer::new(board.TIMER0);
+
+    #[cfg(feature = "v1")]
+    let i2c = { twi::Twi::new(board.TWI0, board.i2c.into(), FREQUENCY_A::K100) };
+
+    #[cfg(feature = "v2")]
+    let i2c = { twim::Twim::new(board.TWIM0, board.i2c_internal.into(), FREQUENCY_A::K100) };
+
+    // works with whichever accelerometer the board has
+    let mut accelerometer = match Accelerometer::new(i2c) {
+        Ok(accelerometer) => accelerometer,
+        Err(_) => defmt::panic!("No accelerometer found"),
+    };
+    defmt::info!("found {}", defmt::Debug2Format(&accelerometer.model()));
+
+    loop {
+        // the sensor updates at 50Hz
+        timer.delay_ms(100_u32);
+        if let Ok(acceleration) = accelerometer.acceleration() {
+            defmt::info!(
+                "x {} y {} z {} mg",
+                acceleration.x,
+                acceleration.y,
+                acceleration.z
+            );
+        }
+    }
+}
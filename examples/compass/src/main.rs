@@ -0,0 +1,59 @@
+#![no_std]
+#![no_main]
+
+use defmt_rtt as _;
+use panic_halt as _;
+
+use cortex_m_rt::entry;
+use embedded_hal::delay::DelayNs;
+use microbit::{
+    display::blocking::Display,
+    hal::{nvmc::Nvmc, twim, Timer},
+    input::ButtonInput,
+    motion::{Accelerometer, Calibration, Compass},
+    pac::twim0::frequency::FREQUENCY_A,
+};
+
+// the last flash page keeps the calibration across resets
+const CALIBRATION_PAGE: (usize, usize) = (0x7F000, 4096);
+
+#[entry]
+fn main() -> ! {
+    let mut board = microbit::Board::take().unwrap();
+    let mut timer = Timer::new(board.TIMER0);
+    let mut display = Display::new(board.display_pins);
+
+    let i2c = twim::Twim::new(board.TWIM0, board.i2c_internal.into(), FREQUENCY_A::K100);
+
+    let accelerometer = Accelerometer::new(i2c).unwrap();
+    let mut compass = Compass::new(accelerometer).unwrap();
+
+    // Safety: the program is much smaller than the flash, nothing else uses
+    // its last page
+    let page = unsafe {
+        core::slice::from_raw_parts_mut(CALIBRATION_PAGE.0 as *mut u8, CALIBRATION_PAGE.1)
+    };
+    let mut flash = Nvmc::new(board.NVMC, page);
+
+    // hold button A at reset to calibrate again
+    let recalibrate = board.buttons.button_a.is_pressed();
+    match Calibration::load(&mut flash, 0) {
+        Ok(Some(calibration)) if !recalibrate => compass.set_calibration(Some(calibration)),
+        _ => {
+            let calibration = compass
+                .calibrate(|image| display.show(&mut timer, image, 20))
+                .unwrap();
+            if calibration.save(&mut flash, 0).is_err() {
+                defmt::warn!("could not save the calibration");
+            }
+            display.clear();
+        }
+    }
+
+    loop {
+        timer.delay_ms(200_u32);
+        if let (Ok(Some(heading)), Ok(strength)) = (compass.heading(), compass.field_strength()) {
+            defmt::info!("heading {} degrees, field {} nT", heading, strength);
+        }
+    }
+}
@@ -5,8 +5,8 @@ use defmt_rtt as _;
 use panic_halt as _;
 
 use cortex_m_rt::entry;
-use embedded_hal::digital::{InputPin, OutputPin};
-use microbit::board::Board;
+use embedded_hal::digital::OutputPin;
+use microbit::{board::Board, input::ButtonInput};
 
 #[entry]
 fn main() -> ! {
@@ -18,16 +18,16 @@ fn main() -> ! {
     let mut led2 = board.display_pins.col2;
 
     loop {
-        if let Ok(true) = board.buttons.button_a.is_high() {
-            let _ = led1.set_high();
-        } else {
+        if board.buttons.button_a.is_pressed() {
             let _ = led1.set_low();
+        } else {
+            let _ = led1.set_high();
         }
 
-        if let Ok(true) = board.buttons.button_b.is_high() {
-            let _ = led2.set_high();
-        } else {
+        if board.buttons.button_b.is_pressed() {
             let _ = led2.set_low();
+        } else {
+            let _ = led2.set_high();
         }
     }
 }
@@ -6,7 +6,12 @@ use defmt_rtt as _;
 use panic_halt as _;
 
 use embedded_hal::delay::DelayNs;
-use microbit::{display::blocking::Display, hal::Timer, logo::Logo, Board};
+use microbit::{
+    display::blocking::Display,
+    hal::Timer,
+    logo::{Logo, TouchEvent},
+    Board,
+};
 
 // Shown on the LED matrix while the logo is being touched.
 const SMILEY: [[u8; 5]; 5] = [
@@ -19,35 +24,41 @@ const SMILEY: [[u8; 5]; 5] = [
 
 const BLANK: [[u8; 5]; 5] = [[0; 5]; 5];
 
+// Time spent showing the display and sleeping between two measurements.
+const DISPLAY_MS: u32 = 50;
+const SLEEP_MS: u32 = 20;
+
 #[entry]
 fn main() -> ! {
     let board = Board::take().unwrap();
     let mut timer = Timer::new(board.TIMER0);
     let mut display = Display::new(board.display_pins);
     let mut logo = Logo::new(board.pins.p1_04);
-
-    // Remembering the previous state lets us derive "pressed" and "released"
-    // edge events from the level reported by `is_touched`, the same way the
-    // button examples derive edges from a button's level.
-    let mut was_touched = false;
+    let mut touched = false;
 
     defmt::info!("Touch the micro:bit logo!");
     loop {
-        let touched = logo.is_touched(&mut timer);
-
-        if touched && !was_touched {
-            defmt::info!("pressed");
-        } else if !touched && was_touched {
-            defmt::info!("released");
+        for event in logo.poll(&mut timer, DISPLAY_MS + SLEEP_MS) {
+            match event {
+                TouchEvent::Touched => {
+                    defmt::info!("touched");
+                    touched = true;
+                }
+                TouchEvent::Released => {
+                    defmt::info!("released");
+                    touched = false;
+                }
+                TouchEvent::Tap => defmt::info!("tap"),
+                TouchEvent::LongTouch => defmt::info!("long touch"),
+            }
         }
-        was_touched = touched;
 
         if touched {
-            display.show(&mut timer, SMILEY, 50);
+            display.show(&mut timer, SMILEY, DISPLAY_MS);
         } else {
-            display.show(&mut timer, BLANK, 50);
+            display.show(&mut timer, BLANK, DISPLAY_MS);
         }
 
-        timer.delay_ms(20u32);
+        timer.delay_ms(SLEEP_MS);
     }
 }
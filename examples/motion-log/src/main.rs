@@ -0,0 +1,111 @@
+#![no_std]
+#![no_main]
+
+use defmt_rtt as _;
+use panic_halt as _;
+
+use cortex_m_rt::entry;
+use embedded_hal::delay::DelayNs;
+use microbit::{
+    display::blocking::Display,
+    hal::{
+        nvmc::Nvmc,
+        twim,
+        uarte::{self, Baudrate, Parity},
+        Timer,
+    },
+    input::ButtonInput,
+    motion::{Accelerometer, Compass, MotionLog, Sample},
+    pac::twim0::frequency::FREQUENCY_A,
+};
+
+mod serial_setup;
+use serial_setup::UartePort;
+
+// the flash below the page the compass example keeps its calibration in
+const LOG_PAGES: (usize, usize) = (0x70000, 15 * 4096);
+
+const SAMPLE_PERIOD_MS: u32 = 500;
+const POLL_MS: u32 = 50;
+
+const RECORDING: [[u8; 5]; 5] = [
+    [0, 0, 0, 0, 0],
+    [0, 1, 1, 1, 0],
+    [0, 1, 1, 1, 0],
+    [0, 1, 1, 1, 0],
+    [0, 0, 0, 0, 0],
+];
+
+#[entry]
+fn main() -> ! {
+    let mut board = microbit::Board::take().unwrap();
+    let mut timer = Timer::new(board.TIMER0);
+    let mut display = Display::new(board.display_pins);
+
+    let i2c = twim::Twim::new(board.TWIM0, board.i2c_internal.into(), FREQUENCY_A::K100);
+    let mut serial = UartePort::new(uarte::Uarte::new(
+        board.UARTE0,
+        board.uart.into(),
+        Parity::EXCLUDED,
+        Baudrate::BAUD115200,
+    ));
+
+    let accelerometer = Accelerometer::new(i2c).unwrap();
+    let mut compass = Compass::new(accelerometer).unwrap();
+
+    // Safety: the program is much smaller than the flash, nothing else uses
+    // those pages
+    let pages = unsafe { core::slice::from_raw_parts_mut(LOG_PAGES.0 as *mut u8, LOG_PAGES.1) };
+    let mut log = MotionLog::new(Nvmc::new(board.NVMC, pages), 0, LOG_PAGES.1 as u32).unwrap();
+
+    // hold button B at reset to start a new log
+    if board.buttons.button_b.is_pressed() {
+        log.clear().unwrap();
+    }
+    // carry on the time of an earlier recording
+    let mut time_ms = match log.samples().last() {
+        Some(Ok(sample)) => sample.time_ms + SAMPLE_PERIOD_MS,
+        _ => 0,
+    };
+    defmt::info!("{} samples of room", log.capacity());
+
+    // button A starts and stops recording, button B prints the log as CSV
+    let mut recording = false;
+    let (mut was_a, mut was_b) = (false, false);
+    let mut elapsed_ms = 0;
+    loop {
+        timer.delay_ms(POLL_MS);
+        let (a, b) = (
+            board.buttons.button_a.is_pressed(),
+            board.buttons.button_b.is_pressed(),
+        );
+        if a && !was_a {
+            recording = !recording;
+            if recording {
+                display.show(&mut timer, RECORDING, 100);
+            }
+            display.clear();
+        }
+        if b && !was_b && !recording && log.write_csv(&mut serial).is_err() {
+            defmt::warn!("could not print the log");
+        }
+        (was_a, was_b) = (a, b);
+
+        elapsed_ms += POLL_MS;
+        if !recording || elapsed_ms < SAMPLE_PERIOD_MS {
+            continue;
+        }
+        elapsed_ms = 0;
+        if let Ok(acceleration) = compass.accelerometer().acceleration() {
+            let sample = Sample {
+                time_ms,
+                acceleration,
+                field: compass.field().ok(),
+            };
+            if log.push(&sample).is_err() {
+                defmt::warn!("could not log the sample");
+            }
+        }
+        time_ms += SAMPLE_PERIOD_MS;
+    }
+}
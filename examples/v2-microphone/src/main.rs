@@ -8,11 +8,9 @@ use cortex_m_rt::entry;
 use microbit::{
     board::Board,
     display::blocking::Display,
-    hal::{
-        gpio::{Level, OpenDrainConfig},
-        saadc::SaadcConfig,
-        Saadc, Timer,
-    },
+    hal::Timer,
+    input::ButtonInput,
+    microphone::{Microphone, SoundLevel},
 };
 
 #[entry]
@@ -20,42 +18,34 @@ fn main() -> ! {
     if let Some(board) = Board::take() {
         let mut timer = Timer::new(board.TIMER0);
         let mut display = Display::new(board.display_pins);
+        let mut button_a = board.buttons.button_a;
 
-        // initialize adc
-        let saadc_config = SaadcConfig::default();
-        let mut saadc = Saadc::new(board.ADC, saadc_config);
-        let mut mic_in = board.microphone_pins.mic_in.into_floating_input();
+        // the microphone, and its LED, stay off until we listen
+        let mut idle = Some((board.ADC, Microphone::new(board.microphone_pins)));
+        let mut listening: Option<SoundLevel> = None;
 
-        // enable microphone
-        board
-            .microphone_pins
-            .mic_run
-            .into_open_drain_output(OpenDrainConfig::Disconnect0HighDrive1, Level::High);
-
-        let mut count: u64 = 0;
-        let mut sum: u64 = 0;
-        let mut max_value: u16 = 0;
         loop {
-            let mic_value = saadc
-                .read_channel(&mut mic_in)
-                .expect("could not read value of microphone") as u16;
+            // listen only while button A is held
+            if button_a.is_pressed() {
+                if let Some((saadc, microphone)) = idle.take() {
+                    listening = Some(SoundLevel::new(saadc, microphone));
+                }
+            } else if let Some(mic) = listening.take() {
+                idle = Some(mic.power_down());
+                display.clear();
+            }
 
-            // Smoothen the signal as audio comes in waves
-            max_value = max_value.max(mic_value);
-            sum += mic_value as u64;
-            count += 1;
+            if let Some(level) = listening.as_mut().and_then(|mic| mic.handle_saadc_event()) {
+                defmt::info!("sound level: {}", level);
 
-            if count % 100 == 0 {
-                let avg = (sum / count) as u16;
-                let image = [
-                    [if max_value > avg + 100 { 1 } else { 0 }; 5],
-                    [if max_value > avg + 80 { 1 } else { 0 }; 5],
-                    [if max_value > avg + 60 { 1 } else { 0 }; 5],
-                    [if max_value > avg + 40 { 1 } else { 0 }; 5],
-                    [if max_value > avg + 20 { 1 } else { 0 }; 5],
-                ];
+                // one more row lit for every 50 steps of sound level
+                let mut image = [[0; 5]; 5];
+                for (row, threshold) in image.iter_mut().rev().zip([10, 60, 110, 160, 210]) {
+                    if level > threshold {
+                        *row = [1; 5];
+                    }
+                }
                 display.show(&mut timer, image, 10);
-                max_value = 0;
             }
         }
     }
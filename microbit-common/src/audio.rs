@@ -0,0 +1,280 @@
+//! PCM sample playback on the micro:bit V2 speaker.
+//!
+//! [`PcmPlayer`] streams 8-bit or 16-bit PCM samples, such as short voice
+//! clips or sound effects stored in flash, to the speaker pin. The samples are
+//! converted to PWM duty cycles and played by the PWM peripheral straight from
+//! RAM with EasyDMA, two buffers at a time: while one buffer plays, the PWM
+//! interrupt refills the other.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # use microbit_common as microbit;
+//! use microbit::{
+//!     audio::{Pcm, PcmPlayer},
+//!     Board,
+//! };
+//!
+//! static CLIP: &[u8] = &[128, 160, 190, 210, 220, 210, 190, 160, 128];
+//!
+//! let board = Board::take().unwrap();
+//! let mut player = PcmPlayer::new(board.PWM0, board.speaker_pin);
+//! player.play(Pcm::U8(CLIP), 8_000);
+//!
+//! // in the PWM0 interrupt
+//! player.handle_pwm_event();
+//! ```
+//!
+//! As with the non-blocking [`Display`](crate::display::nonblocking::Display),
+//! the player will usually live in a `Mutex<RefCell<Option<...>>>` shared
+//! between the interrupt handler and the rest of the program. The PWM
+//! interrupt must be unmasked in the NVIC.
+//!
+//! Sound effects from the [`synth`](crate::synth) module can be rendered into
+//! a buffer and played as [`Pcm::I16`].
+//!
+//! # Technical details
+//!
+//! The PWM runs from the 16MHz clock. Each sample is held for one or more PWM
+//! periods so the PWM frequency stays at or above 32kHz, out of the audible
+//! range, whatever the sample rate. That leaves about 500 output levels.
+//!
+//! Sequence 0 and sequence 1 each play one buffer of [`BUFFER_LEN`] samples and
+//! the PWM loops over the two. The `SEQEND` event of a sequence triggers the
+//! refill of its buffer.
+
+use crate::hal::{
+    gpio::{p0::P0_00, Disconnected, Level, Output, Pin, PushPull},
+    pwm::Instance,
+};
+use core::ptr::{addr_of, addr_of_mut};
+
+/// Frequency of the PWM clock, in Hz.
+const PWM_CLOCK_HZ: u32 = 16_000_000;
+
+/// Lowest PWM frequency, so the carrier can't be heard.
+const MIN_PWM_FREQUENCY_HZ: u32 = 32_000;
+
+/// Lowest supported sample rate, in Hz.
+pub const MIN_SAMPLE_RATE_HZ: u32 = 4_000;
+
+/// Highest supported sample rate, in Hz.
+pub const MAX_SAMPLE_RATE_HZ: u32 = 32_000;
+
+/// Default volume of a new [`PcmPlayer`].
+pub const DEFAULT_VOLUME: u8 = 255;
+
+/// Number of samples in each of the two sequence buffers.
+pub const BUFFER_LEN: usize = 256;
+
+/// Sets the polarity of a sequence value so the output is high for the
+/// compare value and low for the rest of the period.
+const FALLING_EDGE: u16 = 0x8000;
+
+// The sequence buffers, in static RAM for EasyDMA like the sequences of
+// `speaker::Speaker`.
+static mut BUFFERS: [[u16; BUFFER_LEN]; 2] = [[FALLING_EDGE; BUFFER_LEN]; 2];
+
+/// PCM samples to play.
+#[derive(Copy, Clone, Debug)]
+pub enum Pcm<'a> {
+    /// Unsigned 8-bit samples, `128` being silence.
+    U8(&'a [u8]),
+    /// Signed 16-bit samples, `0` being silence.
+    I16(&'a [i16]),
+}
+
+impl Pcm<'_> {
+    /// Returns the number of samples.
+    pub fn len(&self) -> usize {
+        match self {
+            Pcm::U8(samples) => samples.len(),
+            Pcm::I16(samples) => samples.len(),
+        }
+    }
+
+    /// Returns `true` if there are no samples.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the sample at `index` as a signed 16-bit value.
+    fn sample(&self, index: usize) -> i16 {
+        match self {
+            Pcm::U8(samples) => (i16::from(samples[index]) - 128) << 8,
+            Pcm::I16(samples) => samples[index],
+        }
+    }
+}
+
+/// Plays PCM samples on the micro:bit V2 speaker.
+///
+/// See the [module documentation](self) for an example.
+pub struct PcmPlayer<'a, T: Instance> {
+    pwm: T,
+    pin: Pin<Output<PushPull>>,
+    volume: u8,
+    pcm: Option<Pcm<'a>>,
+    position: usize,
+    top: u16,
+    // whether each buffer holds samples, rather than only padding
+    filled: [bool; 2],
+}
+
+impl<'a, T: Instance> PcmPlayer<'a, T> {
+    /// Create a [`PcmPlayer`] from a PWM peripheral and the speaker pin,
+    /// exposed by [`Board`](crate::board::Board) as `speaker_pin`.
+    pub fn new(pwm: T, pin: P0_00<Disconnected>) -> Self {
+        let pin = pin.into_push_pull_output(Level::Low).degrade();
+
+        pwm.psel.out[0].write(|w| unsafe { w.bits(pin.psel_bits()) });
+        for out in &pwm.psel.out[1..] {
+            out.write(|w| w.connect().disconnected());
+        }
+        pwm.mode.write(|w| w.updown().up());
+        pwm.prescaler.write(|w| w.prescaler().div_1());
+        pwm.decoder
+            .write(|w| w.load().common().mode().refresh_count());
+        pwm.seq0.enddelay.write(|w| unsafe { w.bits(0) });
+        pwm.seq1.enddelay.write(|w| unsafe { w.bits(0) });
+        pwm.shorts.reset();
+        pwm.enable.write(|w| w.enable().enabled());
+
+        Self {
+            pwm,
+            pin,
+            volume: DEFAULT_VOLUME,
+            pcm: None,
+            position: 0,
+            top: 0,
+            filled: [false; 2],
+        }
+    }
+
+    /// Set the volume, from `0` (silent) to `255`.
+    ///
+    /// This takes effect from the next buffer.
+    pub fn set_volume(&mut self, volume: u8) {
+        self.volume = volume;
+    }
+
+    /// Returns the current volume.
+    pub fn volume(&self) -> u8 {
+        self.volume
+    }
+
+    /// Start playing `pcm` at `sample_rate_hz`, replacing the samples
+    /// currently playing.
+    ///
+    /// This returns immediately; [`handle_pwm_event`](Self::handle_pwm_event)
+    /// keeps the samples flowing. The sample rate is clamped to
+    /// [`MIN_SAMPLE_RATE_HZ`]..=[`MAX_SAMPLE_RATE_HZ`].
+    pub fn play(&mut self, pcm: Pcm<'a>, sample_rate_hz: u32) {
+        self.stop();
+
+        let sample_rate_hz = sample_rate_hz.clamp(MIN_SAMPLE_RATE_HZ, MAX_SAMPLE_RATE_HZ);
+        let periods_per_sample = MIN_PWM_FREQUENCY_HZ.div_ceil(sample_rate_hz);
+        self.top = (PWM_CLOCK_HZ / (sample_rate_hz * periods_per_sample)) as u16;
+        self.pcm = Some(pcm);
+        self.position = 0;
+        self.fill(0);
+        self.fill(1);
+
+        let pwm = &self.pwm;
+        pwm.countertop
+            .write(|w| unsafe { w.countertop().bits(self.top) });
+        for (buffer, seq) in [&pwm.seq0, &pwm.seq1].iter().enumerate() {
+            seq.ptr
+                .write(|w| unsafe { w.bits(addr_of!(BUFFERS[buffer]) as u32) });
+            seq.cnt.write(|w| unsafe { w.bits(BUFFER_LEN as u32) });
+            seq.refresh
+                .write(|w| unsafe { w.bits(periods_per_sample - 1) });
+        }
+        // alternate between the two buffers until stopped
+        pwm.loop_.write(|w| unsafe { w.cnt().bits(0xFFFF) });
+        pwm.events_seqend[0].reset();
+        pwm.events_seqend[1].reset();
+        pwm.intenset.write(|w| w.seqend0().set().seqend1().set());
+        pwm.tasks_seqstart[0].write(|w| unsafe { w.bits(1) });
+    }
+
+    /// Refill the buffer that just finished playing, and stop once all
+    /// samples have been played.
+    ///
+    /// Call this in an interrupt handler for the PWM you're using. This method
+    /// takes care of clearing the PWM's event registers.
+    pub fn handle_pwm_event(&mut self) {
+        for buffer in 0..2 {
+            let event = &self.pwm.events_seqend[buffer];
+            if event.read().bits() == 0 {
+                continue;
+            }
+            event.reset();
+            if self.pcm.is_some() {
+                self.fill(buffer);
+                if !self.filled[0] && !self.filled[1] {
+                    // only padding is left
+                    self.stop();
+                }
+            }
+        }
+    }
+
+    /// Returns `true` while samples are playing.
+    pub fn is_playing(&self) -> bool {
+        self.pcm.is_some()
+    }
+
+    /// Stop playing, if anything is playing.
+    ///
+    /// The PWM only stops at the end of its current period, which this waits
+    /// for, so the buffers can be refilled straight after.
+    pub fn stop(&mut self) {
+        let pwm = &self.pwm;
+        pwm.intenclr
+            .write(|w| w.seqend0().set_bit().seqend1().set_bit());
+        if self.pcm.take().is_some() {
+            pwm.events_stopped.reset();
+            pwm.tasks_stop.write(|w| unsafe { w.bits(1) });
+            while pwm.events_stopped.read().bits() == 0 {}
+            pwm.events_stopped.reset();
+        }
+    }
+
+    /// Stop the PWM and give back the peripheral and the speaker pin.
+    pub fn free(mut self) -> (T, Pin<Output<PushPull>>) {
+        self.stop();
+        self.pwm.enable.write(|w| w.enable().disabled());
+        self.pwm.psel.out[0].write(|w| w.connect().disconnected());
+        (self.pwm, self.pin)
+    }
+
+    /// Convert the next samples into `buffer`, padding it with silence once
+    /// the samples run out.
+    fn fill(&mut self, buffer: usize) {
+        let pcm = match self.pcm {
+            Some(pcm) => pcm,
+            None => return,
+        };
+        let top = i32::from(self.top);
+        let volume = i32::from(self.volume);
+        let silence = FALLING_EDGE | (top / 2) as u16;
+
+        // Safety: the PWM is done with this buffer until it is started again
+        let values = unsafe { &mut (*addr_of_mut!(BUFFERS))[buffer] };
+        let count = BUFFER_LEN.min(pcm.len() - self.position);
+        for (i, value) in values.iter_mut().enumerate() {
+            *value = if i < count {
+                let sample = i32::from(pcm.sample(self.position + i));
+                // scale around the middle of the range, so silence is a 50%
+                // duty cycle whatever the volume
+                let duty = top / 2 + sample * volume / 255 * top / 65_536;
+                FALLING_EDGE | duty.clamp(0, top) as u16
+            } else {
+                silence
+            };
+        }
+        self.position += count;
+        self.filled[buffer] = count > 0;
+    }
+}
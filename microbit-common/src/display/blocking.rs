@@ -64,6 +64,7 @@
 //! Will display an arrow pointing towards the boards usb port.
 //!
 //! For a working example [`examples/display-blocking`](https://github.com/nrf-rs/microbit/tree/main/examples/display-blocking)
+use super::rotation::Rotation;
 use crate::gpio::{DisplayPins, NUM_COLS, NUM_ROWS};
 use crate::hal::gpio::{Output, Pin, PushPull};
 use embedded_hal::{delay::DelayNs, digital::OutputPin};
@@ -84,6 +85,7 @@ const LED_LAYOUT: [[(usize, usize); 5]; 5] = [
 /// Blocking interface to the on board LED display
 pub struct Display {
     delay_ms: u32,
+    rotation: Rotation,
     rows: [LED; NUM_ROWS],
     cols: [LED; NUM_COLS],
 }
@@ -97,6 +99,7 @@ impl Display {
         let (cols, rows) = pins.degrade();
         Display {
             delay_ms: DEFAULT_DELAY_MS,
+            rotation: Rotation::Deg0,
             rows,
             cols,
         }
@@ -122,6 +125,14 @@ impl Display {
         self.delay_ms = 1000 / freq_hz / (NUM_ROWS as u32);
     }
 
+    /// Set the rotation of the images shown from now on
+    ///
+    /// See [`AutoRotation`](super::rotation::AutoRotation) to follow how the
+    /// board is held.
+    pub fn set_rotation(&mut self, rotation: Rotation) {
+        self.rotation = rotation;
+    }
+
     /// Convert 5x5 image to 3x9 matrix
     ///
     /// The pins are represented as a [3x9 matrix on the micro:bit
@@ -139,6 +150,7 @@ impl Display {
 
     /// Display 5x5 image for a given duration
     pub fn show<D: DelayNs>(&mut self, delay: &mut D, led_display: [[u8; 5]; 5], duration_ms: u32) {
+        let led_display = self.rotation.apply(led_display);
         #[cfg(feature = "v1")]
         {
             let led_matrix = Display::image2matrix(led_display);
@@ -2,5 +2,7 @@
 //!
 //! There are two APIs for controlling the LED display, [`blocking`] and [`nonblocking`].
 //! The `blocking` API is the simplest to get started with.
+//! Both can follow how the board is held, see [`rotation`].
 pub mod blocking;
 pub mod nonblocking;
+pub mod rotation;
@@ -1,5 +1,6 @@
 //! Static 5×5 greyscale and black-and-white images.
 
+use super::super::rotation::Rotation;
 use tiny_led_matrix::{Render, MAX_BRIGHTNESS};
 
 /// A 5×5 image supporting the full range of brightnesses for each LED.
@@ -115,3 +116,45 @@ impl Render for &BitImage {
         BitImage::brightness_at(self, x, y)
     }
 }
+
+/// An image rotated by quarter turns.
+///
+/// # Example
+///
+/// ```no_run
+/// # use microbit_common as microbit;
+/// # use microbit::display::{
+/// #     nonblocking::{BitImage, Frame, MicrobitFrame, Rotated},
+/// #     rotation::Rotation,
+/// # };
+/// const ARROW: BitImage = BitImage::new(&[
+///     [0, 0, 1, 0, 0],
+///     [0, 1, 1, 1, 0],
+///     [1, 0, 1, 0, 1],
+///     [0, 0, 1, 0, 0],
+///     [0, 0, 1, 0, 0],
+/// ]);
+/// // upright with the right edge of the board at the bottom
+/// let mut frame = MicrobitFrame::default();
+/// frame.set(&Rotated::new(&ARROW, Rotation::Deg90));
+/// ```
+#[derive(Copy, Clone, Debug)]
+pub struct Rotated<'a, R: ?Sized> {
+    image: &'a R,
+    rotation: Rotation,
+}
+
+impl<'a, R: ?Sized> Rotated<'a, R> {
+    /// Rotates `image` so it appears upright with the board turned by
+    /// `rotation`.
+    pub const fn new(image: &'a R, rotation: Rotation) -> Self {
+        Rotated { image, rotation }
+    }
+}
+
+impl<R: Render + ?Sized> Render for Rotated<'_, R> {
+    fn brightness_at(&self, x: usize, y: usize) -> u8 {
+        let (x, y) = self.rotation.source(x, y);
+        self.image.brightness_at(x, y)
+    }
+}
@@ -164,10 +164,11 @@ mod image;
 mod matrix;
 mod timer;
 
-pub use image::{BitImage, GreyscaleImage};
+pub use image::{BitImage, GreyscaleImage, Rotated};
 pub use matrix::MicrobitFrame;
 use timer::MicrobitDisplayTimer;
 
+use super::rotation::Rotation;
 use crate::{gpio::DisplayPins, hal::timer::Instance};
 
 use control::MicrobitGpio;
@@ -178,6 +179,7 @@ pub struct Display<T: Instance> {
     timer: MicrobitDisplayTimer<T>,
     pins: DisplayPins,
     frame: MicrobitFrame,
+    rotation: Rotation,
 }
 
 impl<T: Instance> Display<T> {
@@ -191,6 +193,7 @@ impl<T: Instance> Display<T> {
             timer: MicrobitDisplayTimer::new(timer),
             pins,
             frame: MicrobitFrame::default(),
+            rotation: Rotation::Deg0,
         };
         display.initialise();
         display
@@ -245,10 +248,23 @@ impl<T: Instance> Display<T> {
     /// ]));
     /// ```
     pub fn show<R: tiny_led_matrix::Render>(&mut self, image: &R) {
-        self.frame.set(image);
+        self.frame.set(&Rotated::new(image, self.rotation));
         self.display.set_frame(&self.frame);
     }
 
+    /// Set the rotation of the images shown from now on
+    ///
+    /// The image currently shown stays as it is, until the next call to
+    /// [`show`](Display::show). Frames passed to
+    /// [`show_frame`](Display::show_frame) are not rotated, use a [`Rotated`]
+    /// image to set them.
+    ///
+    /// See [`AutoRotation`](super::rotation::AutoRotation) to follow how the
+    /// board is held.
+    pub fn set_rotation(&mut self, rotation: Rotation) {
+        self.rotation = rotation;
+    }
+
     /// Clear the display
     ///
     /// This may be called at any time, so long as the code calling it is not interrupting, or
@@ -0,0 +1,201 @@
+//! Rotation of the display, following how the board is held.
+//!
+//! Both displays can show their images rotated by quarter turns, with
+//! [`blocking::Display::set_rotation`](super::blocking::Display::set_rotation)
+//! and
+//! [`nonblocking::Display::set_rotation`](super::nonblocking::Display::set_rotation).
+//! Left alone, they keep the edge connector as the bottom of the image.
+//!
+//! [`AutoRotation`] turns accelerometer samples into the rotation that keeps
+//! images upright, for badges or clocks that get turned around.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # use microbit_common as microbit;
+//! use microbit::{
+//!     display::{blocking::Display, rotation::AutoRotation},
+//!     hal::Timer,
+//!     motion::Accelerometer,
+//!     Board,
+//! };
+//! # #[cfg(feature = "v1")]
+//! # use microbit::{hal::twi::Twi as I2c, pac::twi0::frequency::FREQUENCY_A};
+//! # #[cfg(feature = "v2")]
+//! # use microbit::{hal::twim::Twim as I2c, pac::twim0::frequency::FREQUENCY_A};
+//!
+//! let board = Board::take().unwrap();
+//! let mut timer = Timer::new(board.TIMER0);
+//! let mut display = Display::new(board.display_pins);
+//! # #[cfg(feature = "v1")]
+//! # let i2c = I2c::new(board.TWI0, board.i2c.into(), FREQUENCY_A::K100);
+//! # #[cfg(feature = "v2")]
+//! let i2c = I2c::new(board.TWIM0, board.i2c_internal.into(), FREQUENCY_A::K100);
+//! let mut accelerometer = Accelerometer::new(i2c).unwrap();
+//! let mut rotation = AutoRotation::new();
+//!
+//! let arrow = [
+//!     [0, 0, 1, 0, 0],
+//!     [0, 1, 1, 1, 0],
+//!     [1, 0, 1, 0, 1],
+//!     [0, 0, 1, 0, 0],
+//!     [0, 0, 1, 0, 0],
+//! ];
+//! loop {
+//!     // always points up
+//!     let acceleration = accelerometer.acceleration().unwrap();
+//!     display.set_rotation(rotation.update(acceleration));
+//!     display.show(&mut timer, arrow, 100);
+//! }
+//! ```
+
+use crate::motion::Vector;
+
+/// Acceleration in the plane of the display below which the board is
+/// considered lying flat, in milli-g.
+pub const FLAT_THRESHOLD_MG: i32 = 400;
+
+/// Tangent of 45° plus the hysteresis of 15°, times 1000.
+const SWITCH_TAN_60: i64 = 1732;
+
+/// How far the board is turned clockwise, as seen from the display side.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Rotation {
+    /// Upright, the edge connector at the bottom.
+    #[default]
+    Deg0,
+    /// A quarter turn, the right edge at the bottom.
+    Deg90,
+    /// Upside down, the logo at the bottom.
+    Deg180,
+    /// Three quarter turns, the left edge at the bottom.
+    Deg270,
+}
+
+impl Rotation {
+    /// Returns the position in the image of the LED at (`x`, `y`), so the
+    /// image appears upright.
+    pub const fn source(self, x: usize, y: usize) -> (usize, usize) {
+        match self {
+            Rotation::Deg0 => (x, y),
+            Rotation::Deg90 => (4 - y, x),
+            Rotation::Deg180 => (4 - x, 4 - y),
+            Rotation::Deg270 => (y, 4 - x),
+        }
+    }
+
+    /// Returns what the LEDs show for `image` to appear upright.
+    ///
+    /// ```
+    /// # use microbit_common as microbit;
+    /// use microbit::display::rotation::Rotation;
+    ///
+    /// let arrow = [
+    ///     [0, 0, 1, 0, 0],
+    ///     [0, 1, 1, 1, 0],
+    ///     [1, 0, 1, 0, 1],
+    ///     [0, 0, 1, 0, 0],
+    ///     [0, 0, 1, 0, 0],
+    /// ];
+    /// // with the right edge at the bottom, up is towards the left edge
+    /// assert_eq!(
+    ///     Rotation::Deg90.apply(arrow),
+    ///     [
+    ///         [0, 0, 1, 0, 0],
+    ///         [0, 1, 0, 0, 0],
+    ///         [1, 1, 1, 1, 1],
+    ///         [0, 1, 0, 0, 0],
+    ///         [0, 0, 1, 0, 0],
+    ///     ]
+    /// );
+    /// assert_eq!(Rotation::Deg0.apply(arrow), arrow);
+    /// ```
+    pub fn apply(self, image: [[u8; 5]; 5]) -> [[u8; 5]; 5] {
+        let mut rotated = [[0; 5]; 5];
+        for (y, row) in rotated.iter_mut().enumerate() {
+            for (x, led) in row.iter_mut().enumerate() {
+                let (source_x, source_y) = self.source(x, y);
+                *led = image[source_y][source_x];
+            }
+        }
+        rotated
+    }
+}
+
+/// Chooses the [`Rotation`] of the display from accelerometer samples.
+///
+/// The edge pointing down becomes the bottom of the image. The rotation
+/// only changes once the board is turned 15° past halfway to the next
+/// edge, so it doesn't flicker between two rotations when held at an
+/// angle, and it stays the same while the board lies flat.
+///
+/// ```
+/// # use microbit_common as microbit;
+/// use microbit::{
+///     display::rotation::{AutoRotation, Rotation},
+///     motion::Vector,
+/// };
+///
+/// let mut rotation = AutoRotation::new();
+/// // standing on its edge connector
+/// assert_eq!(rotation.update(Vector::new(0, -1000, 0)), Rotation::Deg0);
+/// // turned clockwise by 50°, a little past halfway to the right edge
+/// assert_eq!(rotation.update(Vector::new(766, -643, 0)), Rotation::Deg0);
+/// // and by 70°
+/// assert_eq!(rotation.update(Vector::new(940, -342, 0)), Rotation::Deg90);
+/// // back to 40°, still past the hysteresis
+/// assert_eq!(rotation.update(Vector::new(643, -766, 0)), Rotation::Deg90);
+/// // laid down flat
+/// assert_eq!(rotation.update(Vector::new(0, 100, -1000)), Rotation::Deg90);
+/// // picked up upside down
+/// assert_eq!(rotation.update(Vector::new(0, 1000, 0)), Rotation::Deg180);
+/// ```
+#[derive(Copy, Clone, Debug, Default)]
+pub struct AutoRotation {
+    rotation: Rotation,
+}
+
+impl AutoRotation {
+    /// Create an automatic rotation, starting upright.
+    pub const fn new() -> Self {
+        Self {
+            rotation: Rotation::Deg0,
+        }
+    }
+
+    /// Returns the current rotation.
+    pub fn rotation(&self) -> Rotation {
+        self.rotation
+    }
+
+    /// Feed the next acceleration, in milli-g, and returns the rotation.
+    pub fn update(&mut self, acceleration: Vector) -> Rotation {
+        // gravity towards each edge of the display
+        let down = |rotation: Rotation| match rotation {
+            Rotation::Deg0 => -acceleration.y,
+            Rotation::Deg90 => acceleration.x,
+            Rotation::Deg180 => acceleration.y,
+            Rotation::Deg270 => -acceleration.x,
+        };
+        let lowest = [
+            Rotation::Deg0,
+            Rotation::Deg90,
+            Rotation::Deg180,
+            Rotation::Deg270,
+        ]
+        .iter()
+        .copied()
+        .max_by_key(|&rotation| down(rotation))
+        .unwrap_or_default();
+
+        let along = i64::from(down(lowest));
+        let current = i64::from(down(self.rotation));
+        if lowest != self.rotation
+            && along > i64::from(FLAT_THRESHOLD_MG)
+            && along * 1000 > current * SWITCH_TAN_60
+        {
+            self.rotation = lowest;
+        }
+        self.rotation
+    }
+}
@@ -0,0 +1,146 @@
+//! Sharing the internal I2C bus between drivers.
+//!
+//! The motion sensors sit on the internal I2C bus, as does the interface
+//! chip of the micro:bit V2, but a driver built on a `Twi` or `Twim` owns
+//! it. The devices of this module let several drivers use one bus in turn:
+//! the bus goes in a cell or a mutex, and each driver gets its own device,
+//! which borrows the bus for each transaction.
+//!
+//! - [`RefCellDevice`] shares a bus in a `RefCell`, for drivers all used
+//!   from the same context, like the main loop.
+//! - [`CriticalSectionDevice`] shares a bus in a `critical_section::Mutex`,
+//!   which can live in a `static` and be used from interrupt handlers too.
+//! - `MutexDevice`, with the `async` feature, shares an asynchronous bus in
+//!   an `embassy_sync` mutex, for async drivers.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # use microbit_common as microbit;
+//! use core::cell::RefCell;
+//! use microbit::{
+//!     i2c::RefCellDevice,
+//!     motion::{Accelerometer, Compass},
+//!     Board,
+//! };
+//! # #[cfg(feature = "v1")]
+//! # use microbit::{hal::twi::Twi as I2c, pac::twi0::frequency::FREQUENCY_A};
+//! # #[cfg(feature = "v2")]
+//! # use microbit::{hal::twim::Twim as I2c, pac::twim0::frequency::FREQUENCY_A};
+//!
+//! let board = Board::take().unwrap();
+//! # #[cfg(feature = "v1")]
+//! # let i2c = I2c::new(board.TWI0, board.i2c.into(), FREQUENCY_A::K100);
+//! # #[cfg(feature = "v2")]
+//! let i2c = I2c::new(board.TWIM0, board.i2c_internal.into(), FREQUENCY_A::K100);
+//! let bus = RefCell::new(i2c);
+//!
+//! let mut accelerometer = Accelerometer::new(RefCellDevice::new(&bus)).unwrap();
+//! let mut compass = Compass::new(Accelerometer::new(RefCellDevice::new(&bus)).unwrap()).unwrap();
+//! // any other driver of a chip on the bus can have its own device
+//! let device = RefCellDevice::new(&bus);
+//!
+//! let acceleration = accelerometer.acceleration().unwrap();
+//! let heading = compass.heading().unwrap();
+//! ```
+
+use core::cell::RefCell;
+use embedded_hal::i2c::{ErrorType, I2c, Operation};
+
+/// An I2C device on a bus shared through a [`RefCell`].
+///
+/// Each transaction borrows the bus mutably, which panics if it is already
+/// borrowed: the drivers sharing the bus must not be used from interrupt
+/// handlers, use a [`CriticalSectionDevice`] for that.
+pub struct RefCellDevice<'a, T> {
+    bus: &'a RefCell<T>,
+}
+
+impl<'a, T> RefCellDevice<'a, T> {
+    /// Create a device on the shared `bus`.
+    pub fn new(bus: &'a RefCell<T>) -> Self {
+        Self { bus }
+    }
+}
+
+impl<T: I2c> ErrorType for RefCellDevice<'_, T> {
+    type Error = T::Error;
+}
+
+impl<T: I2c> I2c for RefCellDevice<'_, T> {
+    fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        self.bus.borrow_mut().transaction(address, operations)
+    }
+}
+
+/// An I2C device on a bus shared through a critical section mutex.
+///
+/// Each transaction runs in a critical section, so the bus can be shared
+/// with drivers used from interrupt handlers, at the cost of delaying
+/// interrupts for the length of a transaction.
+pub struct CriticalSectionDevice<'a, T> {
+    bus: &'a critical_section::Mutex<RefCell<T>>,
+}
+
+impl<'a, T> CriticalSectionDevice<'a, T> {
+    /// Create a device on the shared `bus`.
+    pub fn new(bus: &'a critical_section::Mutex<RefCell<T>>) -> Self {
+        Self { bus }
+    }
+}
+
+impl<T: I2c> ErrorType for CriticalSectionDevice<'_, T> {
+    type Error = T::Error;
+}
+
+impl<T: I2c> I2c for CriticalSectionDevice<'_, T> {
+    fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        critical_section::with(|cs| self.bus.borrow_ref_mut(cs).transaction(address, operations))
+    }
+}
+
+#[cfg(feature = "async")]
+pub use self::mutex::MutexDevice;
+
+#[cfg(feature = "async")]
+mod mutex {
+    use embassy_sync::{blocking_mutex::raw::RawMutex, mutex::Mutex};
+    use embedded_hal_async::i2c::{ErrorType, I2c, Operation};
+
+    /// An asynchronous I2C device on a bus shared through an async mutex.
+    ///
+    /// Each transaction waits for the bus to be free, and holds it until it
+    /// is over, without blocking other tasks meanwhile.
+    pub struct MutexDevice<'a, M: RawMutex, T> {
+        bus: &'a Mutex<M, T>,
+    }
+
+    impl<'a, M: RawMutex, T> MutexDevice<'a, M, T> {
+        /// Create a device on the shared `bus`.
+        pub fn new(bus: &'a Mutex<M, T>) -> Self {
+            Self { bus }
+        }
+    }
+
+    impl<M: RawMutex, T: I2c> ErrorType for MutexDevice<'_, M, T> {
+        type Error = T::Error;
+    }
+
+    impl<M: RawMutex, T: I2c> I2c for MutexDevice<'_, M, T> {
+        async fn transaction(
+            &mut self,
+            address: u8,
+            operations: &mut [Operation<'_>],
+        ) -> Result<(), Self::Error> {
+            self.bus.lock().await.transaction(address, operations).await
+        }
+    }
+}
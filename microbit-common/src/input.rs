@@ -0,0 +1,377 @@
+//! Combined input from the buttons and the logo, with chords.
+//!
+//! Menus and games often react to combinations of inputs such as "A and B
+//! together" or "hold the logo and press A". [`ChordDetector`] turns the raw
+//! state of the inputs into a single stream of [`InputEvent`]s, so this does
+//! not need to be reimplemented on top of the individual drivers.
+//!
+//! # Buttons
+//!
+//! Button A and B are different pins on the micro:bit V1 and V2, with
+//! different types. The [`ButtonInput`] trait is implemented for the button
+//! types of both boards, so code reading the buttons can be written once:
+//!
+//! ```no_run
+//! # use microbit_common as microbit;
+//! use microbit::{input::ButtonInput, Board};
+//!
+//! fn both_pressed<A: ButtonInput, B: ButtonInput>(a: &mut A, b: &mut B) -> bool {
+//!     a.is_pressed() && b.is_pressed()
+//! }
+//!
+//! let mut board = Board::take().unwrap();
+//! let buttons = &mut board.buttons;
+//! if both_pressed(&mut buttons.button_a, &mut buttons.button_b) {
+//!     // A and B are held down
+//! }
+//! ```
+//!
+//! # Chords
+//!
+//! Inputs that go down within the chord window of each other (100 ms by
+//! default) form a single chord, reported once with
+//! [`InputEvent::Pressed`]. Pressing a further input while a chord is held
+//! starts a new, bigger chord, so holding the logo and then pressing A
+//! reports `LOGO` and then `LOGO | A`. Releasing any input of a chord ends it
+//! with [`InputEvent::Released`], which carries how long the chord was held.
+//! Chords held for the hold time (1 s by default) also report
+//! [`InputEvent::Held`] while they are still down.
+//!
+//! [`ChordDetector`] only looks at [`Inputs`] snapshots and elapsed
+//! milliseconds, so it can be tested with scripted input:
+//!
+//! ```
+//! # use microbit_common as microbit;
+//! use microbit::input::{ChordDetector, InputEvent, Inputs};
+//! use std::iter::repeat;
+//!
+//! let mut chords = ChordDetector::new();
+//! let mut events = Vec::new();
+//! // Hold the logo, then press and release A, sampling every 20 ms.
+//! let samples = repeat(Inputs::LOGO)
+//!     .take(6)
+//!     .chain(repeat(Inputs::LOGO | Inputs::A).take(8))
+//!     .chain(vec![Inputs::LOGO, Inputs::NONE]);
+//! for inputs in samples {
+//!     events.extend(chords.update(inputs, 20));
+//! }
+//! assert_eq!(
+//!     events,
+//!     [
+//!         InputEvent::Pressed(Inputs::LOGO),
+//!         InputEvent::Pressed(Inputs::LOGO | Inputs::A),
+//!         InputEvent::Released {
+//!             inputs: Inputs::LOGO | Inputs::A,
+//!             held_ms: 60,
+//!         },
+//!     ]
+//! );
+//! ```
+//!
+//! # Reading the inputs
+//!
+//! [`Inputs::from_buttons`] reads the state of both buttons, and
+//! [`Inputs::with`] adds the logo (or any other touch source):
+//!
+//! ```no_run
+//! # use microbit_common as microbit;
+//! use embedded_hal::delay::DelayNs;
+//! use microbit::{
+//!     hal::Timer,
+//!     input::{ChordDetector, InputEvent, Inputs},
+//!     Board,
+//! };
+//!
+//! let mut board = Board::take().unwrap();
+//! let mut timer = Timer::new(board.TIMER0);
+//! # #[cfg(feature = "v2")]
+//! let mut logo = microbit::logo::Logo::new(board.pins.p1_04);
+//! let mut chords = ChordDetector::new();
+//!
+//! loop {
+//!     let inputs = Inputs::from_buttons(&mut board.buttons);
+//!     # #[cfg(feature = "v2")]
+//!     let inputs = inputs.with(Inputs::LOGO, logo.is_touched(&mut timer));
+//!     for event in chords.update(inputs, 20) {
+//!         if event == InputEvent::Pressed(Inputs::LOGO | Inputs::A) {
+//!             // open the menu
+//!         }
+//!     }
+//!     timer.delay_ms(20);
+//! }
+//! ```
+
+use crate::board::Buttons;
+use crate::gpio::{BTN_A, BTN_B};
+use core::ops::BitOr;
+use embedded_hal::digital::InputPin;
+
+/// A push button on the board.
+///
+/// The buttons on the micro:bit are active low; implementations take care of
+/// that, so [`is_pressed`](ButtonInput::is_pressed) is `true` while the button
+/// is held down.
+pub trait ButtonInput {
+    /// Returns `true` while the button is pressed.
+    fn is_pressed(&mut self) -> bool;
+
+    /// Returns `true` while the button is not pressed.
+    fn is_released(&mut self) -> bool {
+        !self.is_pressed()
+    }
+}
+
+impl ButtonInput for BTN_A {
+    fn is_pressed(&mut self) -> bool {
+        self.is_low().unwrap_or(false)
+    }
+}
+
+impl ButtonInput for BTN_B {
+    fn is_pressed(&mut self) -> bool {
+        self.is_low().unwrap_or(false)
+    }
+}
+
+/// Default time, in milliseconds, within which inputs form a single chord.
+pub const DEFAULT_CHORD_WINDOW_MS: u32 = 100;
+
+/// Default time, in milliseconds, a chord has to be held before
+/// [`InputEvent::Held`] is reported.
+pub const DEFAULT_HOLD_MS: u32 = 1_000;
+
+/// A set of inputs, such as the inputs that are currently down or the inputs
+/// making up a chord.
+///
+/// Sets are combined with `|`, e.g. `Inputs::A | Inputs::B`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Inputs(u8);
+
+impl Inputs {
+    /// No inputs.
+    pub const NONE: Inputs = Inputs(0);
+    /// Button A.
+    pub const A: Inputs = Inputs(1 << 0);
+    /// Button B.
+    pub const B: Inputs = Inputs(1 << 1);
+    /// The touch logo on the micro:bit V2.
+    pub const LOGO: Inputs = Inputs(1 << 2);
+
+    /// Read the state of both buttons.
+    ///
+    /// A pressed button is included in the returned set.
+    pub fn from_buttons(buttons: &mut Buttons) -> Inputs {
+        Inputs::NONE
+            .with(Inputs::A, buttons.button_a.is_pressed())
+            .with(Inputs::B, buttons.button_b.is_pressed())
+    }
+
+    /// Returns this set with `inputs` added if `active` is `true`.
+    pub const fn with(self, inputs: Inputs, active: bool) -> Inputs {
+        if active {
+            Inputs(self.0 | inputs.0)
+        } else {
+            self
+        }
+    }
+
+    /// Returns `true` if the set is empty.
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Returns `true` if every input in `other` is also in this set.
+    pub const fn contains(self, other: Inputs) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Returns `true` if this set and `other` have an input in common.
+    pub const fn intersects(self, other: Inputs) -> bool {
+        self.0 & other.0 != 0
+    }
+
+    const fn difference(self, other: Inputs) -> Inputs {
+        Inputs(self.0 & !other.0)
+    }
+}
+
+impl BitOr for Inputs {
+    type Output = Inputs;
+
+    fn bitor(self, rhs: Inputs) -> Inputs {
+        Inputs(self.0 | rhs.0)
+    }
+}
+
+/// An event reported by [`ChordDetector::update`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum InputEvent {
+    /// A chord has been pressed.
+    Pressed(Inputs),
+    /// A chord has been held for the hold time. Reported once per chord.
+    Held {
+        /// The inputs making up the chord.
+        inputs: Inputs,
+        /// How long the chord has been held so far, in milliseconds.
+        held_ms: u32,
+    },
+    /// One of the inputs of a chord has been released, ending the chord.
+    Released {
+        /// The inputs making up the chord.
+        inputs: Inputs,
+        /// How long the chord was held, in milliseconds.
+        held_ms: u32,
+    },
+}
+
+/// The events produced by a single [`ChordDetector::update`].
+///
+/// A single update can produce at most two events, which are yielded in the
+/// order they happened.
+#[derive(Copy, Clone, Debug)]
+pub struct InputEvents {
+    events: [Option<InputEvent>; 2],
+    next: usize,
+}
+
+impl InputEvents {
+    const fn none() -> Self {
+        Self {
+            events: [None, None],
+            next: 0,
+        }
+    }
+
+    fn push(&mut self, event: InputEvent) {
+        if let Some(slot) = self.events.iter_mut().find(|slot| slot.is_none()) {
+            *slot = Some(event);
+        }
+    }
+}
+
+impl Iterator for InputEvents {
+    type Item = InputEvent;
+
+    fn next(&mut self) -> Option<InputEvent> {
+        let event = self.events.get(self.next).copied().flatten();
+        if event.is_some() {
+            self.next += 1;
+        }
+        event
+    }
+}
+
+/// Turns [`Inputs`] snapshots into chord [`InputEvent`]s.
+///
+/// See the [module documentation](self) for how chords are detected.
+#[derive(Copy, Clone, Debug)]
+pub struct ChordDetector {
+    /// The inputs that were down at the previous update.
+    down: Inputs,
+    /// The chord currently being held, if any.
+    chord: Inputs,
+    /// Time since inputs last went down, while waiting for the chord window.
+    pending_ms: Option<u32>,
+    held_ms: u32,
+    held_reported: bool,
+    chord_window_ms: u32,
+    hold_ms: u32,
+}
+
+impl ChordDetector {
+    /// Create a detector using [`DEFAULT_CHORD_WINDOW_MS`] and
+    /// [`DEFAULT_HOLD_MS`].
+    pub const fn new() -> Self {
+        Self::with_timing(DEFAULT_CHORD_WINDOW_MS, DEFAULT_HOLD_MS)
+    }
+
+    /// Create a detector with a custom chord window and hold time, in
+    /// milliseconds.
+    pub const fn with_timing(chord_window_ms: u32, hold_ms: u32) -> Self {
+        Self {
+            down: Inputs::NONE,
+            chord: Inputs::NONE,
+            pending_ms: None,
+            held_ms: 0,
+            held_reported: false,
+            chord_window_ms,
+            hold_ms,
+        }
+    }
+
+    /// Set the time within which inputs form a single chord, in milliseconds.
+    pub fn set_chord_window_ms(&mut self, chord_window_ms: u32) {
+        self.chord_window_ms = chord_window_ms;
+    }
+
+    /// Set the time a chord has to be held before [`InputEvent::Held`] is
+    /// reported, in milliseconds.
+    pub fn set_hold_ms(&mut self, hold_ms: u32) {
+        self.hold_ms = hold_ms;
+    }
+
+    /// Returns the chord currently being held, or [`Inputs::NONE`].
+    pub fn chord(&self) -> Inputs {
+        self.chord
+    }
+
+    /// Returns how long the current chord has been held, in milliseconds.
+    pub fn held_ms(&self) -> u32 {
+        if self.chord.is_empty() {
+            0
+        } else {
+            self.held_ms
+        }
+    }
+
+    /// Feed the inputs that are down now, `elapsed_ms` milliseconds after the
+    /// previous update, and return the resulting events.
+    pub fn update(&mut self, inputs: Inputs, elapsed_ms: u32) -> InputEvents {
+        let mut events = InputEvents::none();
+        let pressed = inputs.difference(self.down);
+        let released = self.down.difference(inputs);
+
+        if let Some(pending_ms) = self.pending_ms {
+            let pending_ms = pending_ms.saturating_add(elapsed_ms);
+            self.pending_ms = Some(pending_ms);
+            // A release settles the chord early, so quick taps are reported.
+            if pending_ms >= self.chord_window_ms || !released.is_empty() {
+                self.pending_ms = None;
+                self.chord = self.down;
+                self.held_ms = 0;
+                self.held_reported = false;
+                events.push(InputEvent::Pressed(self.chord));
+            }
+        } else if !self.chord.is_empty() {
+            self.held_ms = self.held_ms.saturating_add(elapsed_ms);
+            if !self.held_reported && self.held_ms >= self.hold_ms {
+                self.held_reported = true;
+                events.push(InputEvent::Held {
+                    inputs: self.chord,
+                    held_ms: self.held_ms,
+                });
+            }
+        }
+
+        if self.chord.intersects(released) {
+            events.push(InputEvent::Released {
+                inputs: self.chord,
+                held_ms: self.held_ms,
+            });
+            self.chord = Inputs::NONE;
+        }
+
+        if !pressed.is_empty() {
+            self.pending_ms = Some(0);
+        }
+
+        self.down = inputs;
+        events
+    }
+}
+
+impl Default for ChordDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
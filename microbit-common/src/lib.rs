@@ -22,6 +22,13 @@ pub mod adc;
 pub mod board;
 pub mod display;
 pub mod gpio;
+pub mod i2c;
+pub mod input;
+pub mod motion;
+pub mod music;
+pub mod sensors;
+pub mod synth;
+pub mod touch;
 
 pub use board::Board;
 
@@ -31,5 +38,14 @@ mod v1;
 #[cfg(feature = "v2")]
 mod v2;
 
+#[cfg(feature = "v2")]
+pub mod audio;
+
 #[cfg(feature = "v2")]
 pub mod logo;
+
+#[cfg(feature = "v2")]
+pub mod microphone;
+
+#[cfg(feature = "v2")]
+pub mod speaker;
@@ -24,6 +24,10 @@
 //! delays, [`Logo::is_touched`] takes `&mut self` and a delay provider, unlike
 //! the simple level reads used for the buttons.
 //!
+//! This is the [`TouchMode::Capacitive`] mode of a [`TouchPin`]; the
+//! [`touch`](crate::touch) module has the details and supports the edge
+//! connector pads as well.
+//!
 //! # Example
 //!
 //! ```no_run
@@ -42,34 +46,22 @@
 //! }
 //! ```
 //!
-//! Detecting "pressed" and "released" edge events can be done in user code by
-//! remembering the previous result of [`Logo::is_touched`]; see the
-//! `logo-touch` example.
-//!
-//! # Attribution
+//! # Events
 //!
-//! The charge-timing approach used here is adapted from the MIT-licensed
-//! `pdx-cs-rust-embedded/mb2-touch` demonstration by the Portland State
-//! University CS Rust Embedded group.
+//! Instead of comparing successive [`Logo::is_touched`] results yourself,
+//! [`Logo::poll`] reports [`TouchEvent`]s: [`Touched`](TouchEvent::Touched)
+//! and [`Released`](TouchEvent::Released) for every edge,
+//! [`Tap`](TouchEvent::Tap) for a short touch and
+//! [`LongTouch`](TouchEvent::LongTouch) once a touch has been held long enough.
+//! See [`TouchTracker`] for how they are derived.
 
-use crate::hal::gpio::{p1::P1_04, Disconnected, Floating, Input, Level, Pin};
+use crate::hal::gpio::{p1::P1_04, Disconnected, Floating, Input, Pin};
+use crate::touch::{TouchMode, TouchPin};
 use embedded_hal::delay::DelayNs;
-use embedded_hal::digital::InputPin;
-
-/// Microseconds the pad is driven low to drain it before each measurement.
-const RESET_TIME_US: u32 = 10;
-
-/// Ceiling on the charge-time count, in microseconds. The measurement loop
-/// stops here so a stuck-low pin can never hang the caller.
-const MAX_TICKS: u32 = 5_000;
 
-/// Charge time (µs) above which an untouched logo becomes "touched".
-const TOUCH_HIGH: u32 = 50;
-
-/// Charge time (µs) below which a touched logo becomes "released". Keeping this
-/// lower than [`TOUCH_HIGH`] gives hysteresis, which prevents the reported
-/// state from flickering when the charge time hovers near the boundary.
-const TOUCH_LOW: u32 = 30;
+pub use crate::touch::{
+    TouchEvent, TouchEvents, TouchTracker, DEFAULT_LONG_TOUCH_MS, DEFAULT_TAP_MAX_MS,
+};
 
 /// The capacitive touch logo on the front of the micro:bit v2.
 ///
@@ -77,10 +69,7 @@ const TOUCH_LOW: u32 = 30;
 /// poll it with [`Logo::is_touched`]. See the [module documentation](self) for
 /// details on how detection works.
 pub struct Logo {
-    // Held as an `Option` so the pin can be temporarily moved out to flip its
-    // direction (output to charge, input to measure) on each measurement.
-    pin: Option<Pin<Input<Floating>>>,
-    touched: bool,
+    touch: TouchPin,
 }
 
 impl Logo {
@@ -90,8 +79,16 @@ impl Logo {
     /// [`Board::pins`](crate::board::Board) as `pins.p1_04`.
     pub fn new(pin: P1_04<Disconnected>) -> Self {
         Self {
-            pin: Some(pin.into_floating_input().degrade()),
-            touched: false,
+            touch: TouchPin::new(pin, TouchMode::Capacitive),
+        }
+    }
+
+    /// Create a [`Logo`] with custom [`TouchEvent`] timing thresholds.
+    ///
+    /// See [`TouchTracker::with_timing`] for the meaning of the thresholds.
+    pub fn with_timing(pin: P1_04<Disconnected>, tap_max_ms: u32, long_touch_ms: u32) -> Self {
+        Self {
+            touch: TouchPin::with_timing(pin, TouchMode::Capacitive, tap_max_ms, long_touch_ms),
         }
     }
 
@@ -118,41 +115,45 @@ impl Logo {
     /// }
     /// ```
     pub fn is_touched<D: DelayNs>(&mut self, delay: &mut D) -> bool {
-        let charge_time = self.sense(delay);
-        self.touched = if self.touched {
-            charge_time > TOUCH_LOW
-        } else {
-            charge_time > TOUCH_HIGH
-        };
-        self.touched
+        self.touch.is_touched(delay)
     }
 
-    /// Perform one raw measurement: drain the pad, then return the time in
-    /// microseconds for it to charge back to a logic high (capped at
-    /// [`MAX_TICKS`]). A larger value means more capacitance, i.e. a touch.
+    /// Perform one measurement and return the resulting [`TouchEvent`]s.
     ///
-    /// The pin is moved out of `self.pin` to flip its direction and is always
-    /// stored back before returning, so `self.pin` is `Some` between calls and
-    /// this method never panics. If the pin were somehow absent, it reports `0`
-    /// (untouched), which is the safe default.
-    fn sense<D: DelayNs>(&mut self, delay: &mut D) -> u32 {
-        let Some(pin) = self.pin.take() else {
-            return 0;
-        };
-
-        // Drive low to drain the pad to 0 V.
-        let out = pin.into_push_pull_output(Level::Low);
-        delay.delay_us(RESET_TIME_US);
-
-        // Switch to a floating input and time the charge back up to high.
-        let mut input = out.into_floating_input();
-        let mut count = 0u32;
-        while count < MAX_TICKS && input.is_low().unwrap_or(false) {
-            delay.delay_us(1);
-            count += 1;
-        }
+    /// `elapsed_ms` is the time since the previous call, which is used to tell
+    /// taps from long touches. Call this regularly (every 10 to 50 ms works
+    /// well) and avoid mixing it with [`Logo::is_touched`], which does not
+    /// account for elapsed time.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use microbit_common as microbit;
+    /// use embedded_hal::delay::DelayNs;
+    /// use microbit::Board;
+    /// use microbit::hal::Timer;
+    /// use microbit::logo::{Logo, TouchEvent};
+    ///
+    /// let board = Board::take().unwrap();
+    /// let mut timer = Timer::new(board.TIMER0);
+    /// let mut logo = Logo::new(board.pins.p1_04);
+    /// loop {
+    ///     for event in logo.poll(&mut timer, 20) {
+    ///         match event {
+    ///             TouchEvent::Tap => { /* short touch */ }
+    ///             TouchEvent::LongTouch => { /* held for a second */ }
+    ///             _ => {}
+    ///         }
+    ///     }
+    ///     timer.delay_ms(20);
+    /// }
+    /// ```
+    pub fn poll<D: DelayNs>(&mut self, delay: &mut D, elapsed_ms: u32) -> TouchEvents {
+        self.touch.poll(delay, elapsed_ms)
+    }
 
-        self.pin = Some(input);
-        count
+    /// Release the logo pin, left configured as a floating input.
+    pub fn free(self) -> Pin<Input<Floating>> {
+        self.touch.free()
     }
 }
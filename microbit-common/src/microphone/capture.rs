@@ -0,0 +1,219 @@
+//! Continuous capture of raw microphone samples.
+
+use super::{configure_saadc, stop_saadc, Microphone, Off, On};
+use crate::hal::{pac::SAADC, ppi::ConfigurablePpi, timer::Instance};
+
+/// Lowest supported capture sample rate, in Hz.
+pub const MIN_CAPTURE_RATE_HZ: u32 = 8_000;
+
+/// Highest supported capture sample rate, in Hz.
+pub const MAX_CAPTURE_RATE_HZ: u32 = 16_000;
+
+/// Frequency of the timer clock, in Hz.
+const TIMER_CLOCK_HZ: u32 = 1_000_000;
+
+/// Captures raw microphone samples at a steady rate, for audio analysis.
+///
+/// A TIMER triggers each SAADC sample through a PPI channel, so the rate
+/// doesn't drift with interrupt latency. EasyDMA writes the samples into two
+/// buffers of `N` samples in turn; a second PPI channel starts the next
+/// buffer as soon as one is full, so no sample is lost between buffers.
+///
+/// Each full buffer is handed over by
+/// [`handle_saadc_event`](Capture::handle_saadc_event), to be called from the
+/// SAADC interrupt. It stays untouched until the other buffer is full, so
+/// process it or copy it to a queue for the main loop before then.
+///
+/// # Example
+///
+/// ```no_run
+/// # use microbit_common as microbit;
+/// use microbit::{
+///     hal::ppi,
+///     microphone::{Capture, Microphone},
+///     Board,
+/// };
+///
+/// static mut BUFFERS: [[i16; 256]; 2] = [[0; 256]; 2];
+///
+/// let board = Board::take().unwrap();
+/// let ppi = ppi::Parts::new(board.PPI);
+/// // Safety: the only reference to the buffers
+/// let buffers = unsafe { &mut *core::ptr::addr_of_mut!(BUFFERS) };
+/// let mut capture = Capture::new(
+///     board.ADC,
+///     Microphone::new(board.microphone_pins),
+///     board.TIMER1,
+///     (ppi.ppi0, ppi.ppi1),
+///     buffers,
+///     16_000,
+/// );
+///
+/// // in the SAADC interrupt
+/// if let Some(samples) = capture.handle_saadc_event() {
+///     // 256 samples, 16ms of sound
+/// }
+/// ```
+pub struct Capture<T: Instance, P0: ConfigurablePpi, P1: ConfigurablePpi, const N: usize> {
+    saadc: SAADC,
+    microphone: Microphone<On>,
+    timer: T,
+    ppi: (P0, P1),
+    buffers: &'static mut [[i16; N]; 2],
+    // buffer EasyDMA is currently writing to
+    filling: usize,
+    sample_rate_hz: u32,
+}
+
+impl<T: Instance, P0: ConfigurablePpi, P1: ConfigurablePpi, const N: usize> Capture<T, P0, P1, N> {
+    /// Power the microphone and start capturing at `sample_rate_hz`, clamped
+    /// to [`MIN_CAPTURE_RATE_HZ`]..=[`MAX_CAPTURE_RATE_HZ`].
+    ///
+    /// The first PPI channel connects the timer to the SAADC sample task,
+    /// the second chains the buffers. The END and STARTED interrupts of the
+    /// SAADC are enabled.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `N` is zero or larger than the SAADC can write at once.
+    pub fn new(
+        saadc: SAADC,
+        microphone: Microphone<Off>,
+        timer: T,
+        ppi: (P0, P1),
+        buffers: &'static mut [[i16; N]; 2],
+        sample_rate_hz: u32,
+    ) -> Self {
+        assert!(N > 0 && N <= 0x7FFF, "unsupported capture buffer length");
+        let sample_rate_hz = sample_rate_hz.clamp(MIN_CAPTURE_RATE_HZ, MAX_CAPTURE_RATE_HZ);
+
+        let microphone = microphone.power_up();
+
+        configure_saadc(&saadc);
+        // each sample is triggered from outside
+        saadc.samplerate.write(|w| w.mode().task());
+        saadc
+            .result
+            .maxcnt
+            .write(|w| unsafe { w.maxcnt().bits(N as u16) });
+
+        let timer0 = timer.as_timer0();
+        timer0.tasks_stop.write(|w| unsafe { w.bits(1) });
+        timer0.tasks_clear.write(|w| unsafe { w.bits(1) });
+        timer0.mode.write(|w| w.mode().timer());
+        timer0.bitmode.write(|w| w.bitmode()._32bit());
+        // set frequency to 1MHz
+        timer0.prescaler.write(|w| unsafe { w.bits(4) });
+        timer0.cc[0].write(|w| unsafe { w.bits(TIMER_CLOCK_HZ / sample_rate_hz) });
+        timer0.shorts.write(|w| w.compare0_clear().enabled());
+
+        let (mut sample, mut chain) = ppi;
+        sample.set_event_endpoint(&timer0.events_compare[0]);
+        sample.set_task_endpoint(&saadc.tasks_sample);
+        chain.set_event_endpoint(&saadc.events_end);
+        chain.set_task_endpoint(&saadc.tasks_start);
+
+        let mut capture = Self {
+            saadc,
+            microphone,
+            timer,
+            ppi: (sample, chain),
+            buffers,
+            filling: 0,
+            sample_rate_hz,
+        };
+        capture.start();
+        capture
+    }
+
+    /// Returns the sample rate, in Hz.
+    pub fn sample_rate_hz(&self) -> u32 {
+        self.sample_rate_hz
+    }
+
+    /// Returns the buffer that was just filled, if any, and queue the next
+    /// one.
+    ///
+    /// Call this in the SAADC interrupt handler. This method takes care of
+    /// clearing the SAADC's event registers.
+    pub fn handle_saadc_event(&mut self) -> Option<&[i16; N]> {
+        let saadc = &self.saadc;
+        let mut filled = None;
+        if saadc.events_end.read().bits() != 0 {
+            saadc.events_end.reset();
+            // the PPI has already started the other buffer
+            filled = Some(self.filling);
+            self.filling ^= 1;
+        }
+        if saadc.events_started.read().bits() != 0 {
+            saadc.events_started.reset();
+            // the pointer is latched by START, so this is for the next buffer
+            self.set_next_buffer();
+        }
+        filled.map(move |index| &self.buffers[index])
+    }
+
+    /// Stop capturing, power the microphone down and give back the
+    /// peripherals, the microphone and the buffers.
+    #[allow(clippy::type_complexity)]
+    pub fn power_down(
+        mut self,
+    ) -> (
+        SAADC,
+        Microphone<Off>,
+        T,
+        (P0, P1),
+        &'static mut [[i16; N]; 2],
+    ) {
+        self.timer
+            .as_timer0()
+            .tasks_stop
+            .write(|w| unsafe { w.bits(1) });
+        self.ppi.0.disable();
+        self.ppi.1.disable();
+        self.saadc
+            .intenclr
+            .write(|w| w.end().set_bit().started().set_bit());
+        stop_saadc(&self.saadc);
+
+        (
+            self.saadc,
+            self.microphone.power_down(),
+            self.timer,
+            self.ppi,
+            self.buffers,
+        )
+    }
+
+    fn start(&mut self) {
+        self.filling = 0;
+        let first = self.buffers[0].as_mut_ptr() as u32;
+        self.saadc
+            .result
+            .ptr
+            .write(|w| unsafe { w.ptr().bits(first) });
+        self.saadc.events_started.reset();
+        self.saadc.events_end.reset();
+        self.saadc.tasks_start.write(|w| unsafe { w.bits(1) });
+        while self.saadc.events_started.read().bits() == 0 {}
+        self.saadc.events_started.reset();
+        self.set_next_buffer();
+
+        self.saadc.intenset.write(|w| w.end().set().started().set());
+        self.ppi.0.enable();
+        self.ppi.1.enable();
+        self.timer
+            .as_timer0()
+            .tasks_start
+            .write(|w| unsafe { w.bits(1) });
+    }
+
+    /// Point EasyDMA at the buffer to fill after the current one.
+    fn set_next_buffer(&mut self) {
+        let next = self.buffers[self.filling ^ 1].as_mut_ptr();
+        self.saadc
+            .result
+            .ptr
+            .write(|w| unsafe { w.ptr().bits(next as u32) });
+    }
+}
@@ -0,0 +1,136 @@
+//! Loud and quiet events from a stream of sound levels.
+
+/// Default level at or above which it is loud.
+pub const DEFAULT_LOUD_THRESHOLD: u8 = 128;
+
+/// Default level at or below which it is quiet again.
+pub const DEFAULT_QUIET_THRESHOLD: u8 = 64;
+
+/// Default time the level must stay past a threshold before the event is
+/// reported, in milliseconds.
+pub const DEFAULT_MIN_DURATION_MS: u32 = 0;
+
+/// Events reported by [`SoundDetector::update`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SoundEvent {
+    /// The level rose to the loud threshold.
+    Loud,
+    /// The level fell back to the quiet threshold.
+    Quiet,
+}
+
+/// Turns sound levels into [`SoundEvent`]s, like the micro:bit V2
+/// `input.onSound` API.
+///
+/// It becomes loud when the level reaches the loud threshold and quiet again
+/// only once the level drops to the lower quiet threshold, so a level
+/// hovering around one threshold doesn't produce a stream of events. The
+/// level must also stay past the threshold for a minimum duration.
+///
+/// ```
+/// # use microbit_common as microbit;
+/// use microbit::microphone::{SoundDetector, SoundEvent};
+///
+/// let mut detector = SoundDetector::new();
+/// detector.set_thresholds(150, 50);
+/// detector.set_min_duration_ms(200);
+///
+/// // levels every 100ms: a short bang, a long noise, then a slow fade
+/// let levels = [20, 200, 20, 180, 180, 180, 120, 180, 100, 40, 30, 30];
+/// let events: Vec<_> = levels
+///     .iter()
+///     .filter_map(|&level| detector.update(level, 100))
+///     .collect();
+/// assert_eq!(events, [SoundEvent::Loud, SoundEvent::Quiet]);
+/// ```
+#[derive(Copy, Clone, Debug)]
+pub struct SoundDetector {
+    loud_threshold: u8,
+    quiet_threshold: u8,
+    min_duration_ms: u32,
+    loud: bool,
+    // time the level has been past the threshold that would change the state
+    pending_ms: Option<u32>,
+}
+
+impl SoundDetector {
+    /// Create a quiet detector with the default thresholds and minimum
+    /// duration.
+    pub const fn new() -> Self {
+        Self {
+            loud_threshold: DEFAULT_LOUD_THRESHOLD,
+            quiet_threshold: DEFAULT_QUIET_THRESHOLD,
+            min_duration_ms: DEFAULT_MIN_DURATION_MS,
+            loud: false,
+            pending_ms: None,
+        }
+    }
+
+    /// Set the loud and quiet thresholds.
+    ///
+    /// The quiet threshold is lowered to the loud one if it is higher.
+    pub fn set_thresholds(&mut self, loud: u8, quiet: u8) {
+        self.loud_threshold = loud;
+        self.quiet_threshold = quiet.min(loud);
+    }
+
+    /// Returns the loud threshold.
+    pub fn loud_threshold(&self) -> u8 {
+        self.loud_threshold
+    }
+
+    /// Returns the quiet threshold.
+    pub fn quiet_threshold(&self) -> u8 {
+        self.quiet_threshold
+    }
+
+    /// Set how long the level must stay past a threshold before the event
+    /// is reported, in milliseconds.
+    pub fn set_min_duration_ms(&mut self, min_duration_ms: u32) {
+        self.min_duration_ms = min_duration_ms;
+    }
+
+    /// Returns `true` between a [`SoundEvent::Loud`] and the following
+    /// [`SoundEvent::Quiet`].
+    pub fn is_loud(&self) -> bool {
+        self.loud
+    }
+
+    /// Feed the next sound level, measured `elapsed_ms` milliseconds after
+    /// the previous one.
+    pub fn update(&mut self, level: u8, elapsed_ms: u32) -> Option<SoundEvent> {
+        let past_threshold = if self.loud {
+            level <= self.quiet_threshold
+        } else {
+            level >= self.loud_threshold
+        };
+        if !past_threshold {
+            self.pending_ms = None;
+            return None;
+        }
+
+        // the first level past the threshold starts the count
+        let pending_ms = match self.pending_ms {
+            Some(pending_ms) => pending_ms.saturating_add(elapsed_ms),
+            None => 0,
+        };
+        if pending_ms < self.min_duration_ms {
+            self.pending_ms = Some(pending_ms);
+            return None;
+        }
+
+        self.pending_ms = None;
+        self.loud = !self.loud;
+        Some(if self.loud {
+            SoundEvent::Loud
+        } else {
+            SoundEvent::Quiet
+        })
+    }
+}
+
+impl Default for SoundDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,154 @@
+//! Sound level computation, independent of the hardware.
+
+/// Default length of the window the sound level is averaged over, in
+/// milliseconds.
+pub const DEFAULT_WINDOW_MS: u32 = 100;
+
+/// Level in dB (relative to one ADC count) mapped to a sound level of `0`.
+const FLOOR_DB: u32 = 12;
+
+/// Level in dB (relative to one ADC count) mapped to a sound level of `255`,
+/// a full scale 12-bit signal.
+const FULL_SCALE_DB: u32 = 66;
+
+/// How fast the DC offset estimate follows the signal, as a shift: the
+/// estimate moves by `1 / (1 << DC_SHIFT)` of the difference each sample.
+const DC_SHIFT: u32 = 8;
+
+/// Computes a sound level from raw microphone samples.
+///
+/// The level is the RMS of the samples, after removing their DC offset, on a
+/// logarithmic scale from `0` (silence) to `255` (the loudest the ADC can
+/// measure), like MakeCode's `input.soundLevel()`. Each step is about 0.2 dB.
+///
+/// The meter doesn't touch any hardware, so it can be fed recorded or
+/// generated samples on the host:
+///
+/// ```
+/// # use microbit_common as microbit;
+/// use microbit::microphone::LevelMeter;
+///
+/// // a square wave of the given amplitude around a DC offset of 2048
+/// fn tone(amplitude: i16) -> Vec<i16> {
+///     (0..1000).map(|i| if i % 20 < 10 { 2048 + amplitude } else { 2048 - amplitude }).collect()
+/// }
+///
+/// let mut meter = LevelMeter::new(10_000);
+/// meter.set_window_ms(100);
+///
+/// // let the DC estimate settle
+/// meter.update(&[2048; 2000]);
+/// assert_eq!(meter.level(), 0);
+///
+/// assert!(meter.update(&tone(50)).is_some());
+/// let quiet = meter.level();
+/// meter.update(&tone(500));
+/// let loud = meter.level();
+/// assert!(0 < quiet && quiet < loud && loud < 255);
+/// ```
+#[derive(Copy, Clone, Debug)]
+pub struct LevelMeter {
+    sample_rate_hz: u32,
+    window: u32,
+    count: u32,
+    sum_squares: u64,
+    // DC offset estimate, with DC_SHIFT fractional bits
+    dc: i32,
+    settled: bool,
+    level: u8,
+}
+
+impl LevelMeter {
+    /// Create a meter for samples taken at `sample_rate_hz`, averaging over
+    /// [`DEFAULT_WINDOW_MS`].
+    pub const fn new(sample_rate_hz: u32) -> Self {
+        Self {
+            sample_rate_hz,
+            window: window_samples(sample_rate_hz, DEFAULT_WINDOW_MS),
+            count: 0,
+            sum_squares: 0,
+            dc: 0,
+            settled: false,
+            level: 0,
+        }
+    }
+
+    /// Set the length of the window the level is averaged over, in
+    /// milliseconds.
+    ///
+    /// Longer windows give a steadier level, shorter ones react faster.
+    pub fn set_window_ms(&mut self, window_ms: u32) {
+        self.window = window_samples(self.sample_rate_hz, window_ms);
+        self.count = 0;
+        self.sum_squares = 0;
+    }
+
+    /// Returns the length of the averaging window, in milliseconds.
+    pub fn window_ms(&self) -> u32 {
+        self.window * 1000 / self.sample_rate_hz.max(1)
+    }
+
+    /// Returns the level of the last complete window, from `0` to `255`.
+    pub fn level(&self) -> u8 {
+        self.level
+    }
+
+    /// Feed raw samples to the meter.
+    ///
+    /// Returns the new level each time a window completes, the last one if
+    /// several complete.
+    pub fn update(&mut self, samples: &[i16]) -> Option<u8> {
+        let mut new_level = None;
+        for &sample in samples {
+            let sample = i32::from(sample);
+            if !self.settled {
+                // start from the first sample rather than zero
+                self.dc = sample << DC_SHIFT;
+                self.settled = true;
+            }
+            self.dc += sample - (self.dc >> DC_SHIFT);
+            let ac = i64::from(sample - (self.dc >> DC_SHIFT));
+            self.sum_squares += (ac * ac) as u64;
+            self.count += 1;
+
+            if self.count >= self.window {
+                self.level = level(self.sum_squares / u64::from(self.count));
+                self.count = 0;
+                self.sum_squares = 0;
+                new_level = Some(self.level);
+            }
+        }
+        new_level
+    }
+}
+
+/// Number of samples in a window of `window_ms`, at least one.
+const fn window_samples(sample_rate_hz: u32, window_ms: u32) -> u32 {
+    let samples = sample_rate_hz / 1000 * window_ms;
+    if samples == 0 {
+        1
+    } else {
+        samples
+    }
+}
+
+/// Map a mean square value to a level from `0` to `255`.
+pub(super) fn level(mean_square: u64) -> u8 {
+    // 10 * log10(x) = 3.0103 * log2(x), kept with 8 fractional bits
+    let db = log2_q8(mean_square) * 771 / 256;
+    let floor = FLOOR_DB << 8;
+    let full_scale = FULL_SCALE_DB << 8;
+    let db = db.clamp(floor, full_scale);
+    ((db - floor) * 255 / (full_scale - floor)) as u8
+}
+
+/// Base 2 logarithm with 8 fractional bits, `0` for `0`.
+fn log2_q8(x: u64) -> u32 {
+    if x == 0 {
+        return 0;
+    }
+    let integer = 63 - x.leading_zeros();
+    // the 8 bits after the leading one approximate the fractional part
+    let fraction = ((x << (63 - integer)) >> 55) & 0xFF;
+    (integer << 8) | fraction as u32
+}
@@ -0,0 +1,348 @@
+//! The micro:bit V2 microphone.
+//!
+//! [`SoundLevel`] measures how loud it is around the board, like MakeCode's
+//! `input.soundLevel()`. It owns the [`Microphone`] and the SAADC, which
+//! samples the microphone continuously on its own timer and hands over a
+//! block of samples at a time with EasyDMA.
+//!
+//! The microphone is only powered, and its LED only lit, while a driver is
+//! sampling it. Drivers take a [`Microphone<Off>`] and give it back from
+//! their `power_down` method; there is no way to read an unpowered
+//! microphone.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # use microbit_common as microbit;
+//! use microbit::{
+//!     microphone::{Microphone, SoundLevel},
+//!     Board,
+//! };
+//!
+//! let board = Board::take().unwrap();
+//! let microphone = Microphone::new(board.microphone_pins);
+//! // powers the microphone up
+//! let mut mic = SoundLevel::new(board.ADC, microphone);
+//!
+//! for _ in 0..1000 {
+//!     // in the SAADC interrupt, or polled like here
+//!     if let Some(level) = mic.handle_saadc_event() {
+//!         // 0 is silent, 255 is as loud as the microphone can tell
+//!     }
+//! }
+//!
+//! // powers the microphone down, until it is given to a driver again
+//! let (saadc, microphone) = mic.power_down();
+//! ```
+//!
+//! [`SoundLevel`] also reports [`SoundEvent::Loud`] and [`SoundEvent::Quiet`]
+//! events when the level crosses configurable thresholds, like the V2
+//! `input.onSound` API:
+//!
+//! ```no_run
+//! # use microbit_common as microbit;
+//! # use microbit::{microphone::{Microphone, SoundLevel}, Board};
+//! # let board = Board::take().unwrap();
+//! # let mut mic = SoundLevel::new(board.ADC, Microphone::new(board.microphone_pins));
+//! use microbit::microphone::SoundEvent;
+//!
+//! let mut light = false;
+//! mic.set_thresholds(160, 80);
+//! loop {
+//!     mic.handle_saadc_event();
+//!     if mic.take_event() == Some(SoundEvent::Loud) {
+//!         // clap to toggle
+//!         light = !light;
+//!     }
+//! }
+//! ```
+//!
+//! The level computation and the event detection live in [`LevelMeter`] and
+//! [`SoundDetector`], which don't touch any hardware and can be tested on the
+//! host.
+//!
+//! For audio analysis, [`Capture`] instead hands over every raw sample, taken
+//! at a steady rate between [`MIN_CAPTURE_RATE_HZ`] and
+//! [`MAX_CAPTURE_RATE_HZ`], in double buffers.
+//!
+//! Those buffers can be fed to a [`Spectrum`], which finds the dominant
+//! frequency and the level of [`BANDS`] octave bands, ready to show with
+//! [`bar_graph`], or to a [`Goertzel`] detector for a single frequency:
+//!
+//! ```no_run
+//! # use microbit_common as microbit;
+//! # use microbit::{hal::ppi, microphone::{Capture, Microphone}, Board};
+//! # static mut BUFFERS: [[i16; 256]; 2] = [[0; 256]; 2];
+//! # let board = Board::take().unwrap();
+//! # let ppi = ppi::Parts::new(board.PPI);
+//! # let buffers = unsafe { &mut *core::ptr::addr_of_mut!(BUFFERS) };
+//! # let mut capture = Capture::new(
+//! #     board.ADC,
+//! #     Microphone::new(board.microphone_pins),
+//! #     board.TIMER1,
+//! #     (ppi.ppi0, ppi.ppi1),
+//! #     buffers,
+//! #     16_000,
+//! # );
+//! use microbit::microphone::{bar_graph, Spectrum};
+//!
+//! let mut spectrum = Spectrum::<256>::new(capture.sample_rate_hz());
+//! loop {
+//!     if let Some(samples) = capture.handle_saadc_event() {
+//!         spectrum.analyse(samples);
+//!         let image = bar_graph(&spectrum.bands());
+//!         // show the image on the display
+//!     }
+//! }
+//! ```
+
+use crate::{gpio::MicrophonePins, hal::pac::SAADC};
+use core::{
+    marker::PhantomData,
+    ptr::{addr_of, addr_of_mut},
+};
+use embedded_hal::digital::OutputPin;
+
+mod capture;
+mod events;
+mod level;
+mod spectrum;
+
+pub use capture::{Capture, MAX_CAPTURE_RATE_HZ, MIN_CAPTURE_RATE_HZ};
+pub use events::{
+    SoundDetector, SoundEvent, DEFAULT_LOUD_THRESHOLD, DEFAULT_MIN_DURATION_MS,
+    DEFAULT_QUIET_THRESHOLD,
+};
+pub use level::{LevelMeter, DEFAULT_WINDOW_MS};
+pub use spectrum::{bar_graph, Goertzel, Spectrum, BANDS, MAX_SPECTRUM_LEN};
+
+/// Rate the microphone is sampled at, in Hz.
+pub const SAMPLE_RATE_HZ: u32 = 10_000;
+
+/// Frequency of the SAADC sample rate timer, in Hz.
+const SAADC_CLOCK_HZ: u32 = 16_000_000;
+
+/// Number of samples in each block handed over by the SAADC.
+const BLOCK_LEN: usize = 64;
+
+// EasyDMA writes the samples to RAM while they are taken, so the block needs
+// a fixed address. There is only one microphone, so there is only ever one
+// driver using it.
+static mut BLOCK: [i16; BLOCK_LEN] = [0; BLOCK_LEN];
+
+/// Type state of an unpowered [`Microphone`].
+pub struct Off;
+
+/// Type state of a powered [`Microphone`], only held by a driver while it is
+/// sampling.
+pub struct On;
+
+/// The micro:bit V2 microphone, powered or not.
+///
+/// Powering the microphone with its `mic_run` pin also lights the
+/// microphone LED next to the logo, so users can tell when the board is
+/// listening. A [`Microphone<Off>`] can only be handed to a driver such as
+/// [`SoundLevel`] or [`Capture`], which powers it up while sampling and
+/// powers it down again from its `power_down` method.
+pub struct Microphone<S> {
+    pins: MicrophonePins,
+    _state: PhantomData<S>,
+}
+
+impl Microphone<Off> {
+    /// Take the microphone pins, making sure the microphone is powered down.
+    pub fn new(mut pins: MicrophonePins) -> Self {
+        pins.mic_run.set_low().ok();
+        Self {
+            pins,
+            _state: PhantomData,
+        }
+    }
+
+    /// Give back the microphone pins.
+    pub fn free(self) -> MicrophonePins {
+        self.pins
+    }
+
+    /// Power the microphone up, lighting its LED.
+    fn power_up(mut self) -> Microphone<On> {
+        self.pins.mic_run.set_high().ok();
+        Microphone {
+            pins: self.pins,
+            _state: PhantomData,
+        }
+    }
+}
+
+impl Microphone<On> {
+    /// Power the microphone down, turning its LED off.
+    fn power_down(mut self) -> Microphone<Off> {
+        self.pins.mic_run.set_low().ok();
+        Microphone {
+            pins: self.pins,
+            _state: PhantomData,
+        }
+    }
+}
+
+impl From<MicrophonePins> for Microphone<Off> {
+    fn from(pins: MicrophonePins) -> Self {
+        Self::new(pins)
+    }
+}
+
+/// Sound level meter for the micro:bit V2 microphone.
+///
+/// See the [module documentation](self) for an example.
+pub struct SoundLevel {
+    saadc: SAADC,
+    microphone: Microphone<On>,
+    meter: LevelMeter,
+    detector: SoundDetector,
+    event: Option<SoundEvent>,
+}
+
+impl SoundLevel {
+    /// Power the microphone and start sampling it at [`SAMPLE_RATE_HZ`].
+    ///
+    /// The END interrupt of the SAADC is enabled; it fires each time a block
+    /// of samples is ready for [`handle_saadc_event`](Self::handle_saadc_event).
+    pub fn new(saadc: SAADC, microphone: Microphone<Off>) -> Self {
+        let microphone = microphone.power_up();
+
+        configure_saadc(&saadc);
+        saadc.samplerate.write(|w| unsafe {
+            w.cc().bits((SAADC_CLOCK_HZ / SAMPLE_RATE_HZ) as u16);
+            w.mode().timers()
+        });
+        saadc
+            .result
+            .ptr
+            .write(|w| unsafe { w.ptr().bits(addr_of_mut!(BLOCK) as u32) });
+        saadc
+            .result
+            .maxcnt
+            .write(|w| unsafe { w.maxcnt().bits(BLOCK_LEN as u16) });
+
+        saadc.events_end.reset();
+        saadc.intenset.write(|w| w.end().set());
+        saadc.tasks_start.write(|w| unsafe { w.bits(1) });
+        while saadc.events_started.read().bits() == 0 {}
+        saadc.events_started.reset();
+        // start the sample rate timer, it runs until stopped
+        saadc.tasks_sample.write(|w| unsafe { w.bits(1) });
+
+        Self {
+            saadc,
+            microphone,
+            meter: LevelMeter::new(SAMPLE_RATE_HZ),
+            detector: SoundDetector::new(),
+            event: None,
+        }
+    }
+
+    /// Set the length of the window the level is averaged over, in
+    /// milliseconds.
+    pub fn set_window_ms(&mut self, window_ms: u32) {
+        self.meter.set_window_ms(window_ms);
+    }
+
+    /// Returns the level of the last complete window, from `0` to `255`.
+    pub fn level(&self) -> u8 {
+        self.meter.level()
+    }
+
+    /// Set the levels at or above which it is loud, and at or below which it
+    /// is quiet again.
+    ///
+    /// See [`SoundDetector::set_thresholds`].
+    pub fn set_thresholds(&mut self, loud: u8, quiet: u8) {
+        self.detector.set_thresholds(loud, quiet);
+    }
+
+    /// Set how long the level must stay past a threshold before the event
+    /// is reported, in milliseconds.
+    pub fn set_min_duration_ms(&mut self, min_duration_ms: u32) {
+        self.detector.set_min_duration_ms(min_duration_ms);
+    }
+
+    /// Returns `true` between a [`SoundEvent::Loud`] and the following
+    /// [`SoundEvent::Quiet`].
+    pub fn is_loud(&self) -> bool {
+        self.detector.is_loud()
+    }
+
+    /// Returns the latest sound event not taken yet, if any.
+    ///
+    /// Only the latest event is kept, so take them at least as often as the
+    /// averaging window completes.
+    pub fn take_event(&mut self) -> Option<SoundEvent> {
+        self.event.take()
+    }
+
+    /// Process the block of samples the SAADC just finished, if any, and
+    /// start the next one.
+    ///
+    /// Returns the new level each time an averaging window completes, which
+    /// is also when sound events are detected.
+    ///
+    /// Call this in the SAADC interrupt handler, or poll it often enough not
+    /// to miss too many blocks. This method takes care of clearing the
+    /// SAADC's event registers.
+    pub fn handle_saadc_event(&mut self) -> Option<u8> {
+        if self.saadc.events_end.read().bits() == 0 {
+            return None;
+        }
+        self.saadc.events_end.reset();
+
+        // Safety: EasyDMA doesn't write to the block until it is started again
+        let block = unsafe { &*addr_of!(BLOCK) };
+        let level = self.meter.update(block);
+        self.saadc.tasks_start.write(|w| unsafe { w.bits(1) });
+
+        if let Some(level) = level {
+            let window_ms = self.meter.window_ms();
+            if let Some(event) = self.detector.update(level, window_ms) {
+                self.event = Some(event);
+            }
+        }
+        level
+    }
+
+    /// Stop sampling, power the microphone down and give back the SAADC and
+    /// the microphone.
+    pub fn power_down(self) -> (SAADC, Microphone<Off>) {
+        self.saadc.intenclr.write(|w| w.end().set_bit());
+        stop_saadc(&self.saadc);
+
+        (self.saadc, self.microphone.power_down())
+    }
+}
+
+/// Enable the SAADC and set channel 0 up to sample the microphone.
+fn configure_saadc(saadc: &SAADC) {
+    saadc.enable.write(|w| w.enable().enabled());
+    saadc.resolution.write(|w| w.val()._12bit());
+    saadc.oversample.write(|w| w.oversample().bypass());
+    saadc.ch[0].config.write(|w| {
+        w.refsel().internal();
+        w.gain().gain1_6();
+        w.tacq()._10us();
+        w.mode().se();
+        w.resp().bypass();
+        w.resn().bypass();
+        w.burst().disabled();
+        w
+    });
+    // P0_05 is AIN3
+    saadc.ch[0].pselp.write(|w| w.pselp().analog_input3());
+    saadc.ch[0].pseln.write(|w| w.pseln().nc());
+}
+
+/// Stop any sampling and disable the SAADC.
+fn stop_saadc(saadc: &SAADC) {
+    saadc.tasks_stop.write(|w| unsafe { w.bits(1) });
+    while saadc.events_stopped.read().bits() == 0 {}
+    saadc.events_stopped.reset();
+    saadc.enable.write(|w| w.enable().disabled());
+}
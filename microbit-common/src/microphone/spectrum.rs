@@ -0,0 +1,327 @@
+//! Spectrum analysis of microphone samples, independent of the hardware.
+
+use super::level::level;
+use crate::synth::sine;
+
+/// Largest number of samples a [`Spectrum`] can analyse at once.
+pub const MAX_SPECTRUM_LEN: usize = 1024;
+
+/// Number of bands of [`Spectrum::bands`], one per column of the display.
+pub const BANDS: usize = 5;
+
+/// Brightness of a fully lit LED in a [`bar_graph`].
+const MAX_BRIGHTNESS: u32 = 9;
+
+/// Frequency spectrum of a block of samples, computed with a fixed-point FFT.
+///
+/// The samples have their mean removed and go through a Hann window before
+/// the FFT, so the magnitude of each bin is roughly the amplitude, in ADC
+/// counts, of a sine at that frequency. Bins are `sample_rate_hz / N` apart
+/// and only the first `N / 2`, up to half the sample rate, are meaningful.
+///
+/// `N` must be a power of two, up to [`MAX_SPECTRUM_LEN`]. The analysis
+/// only uses integer arithmetic, so it runs on the micro:bit as it does on
+/// the host:
+///
+/// ```
+/// # use microbit_common as microbit;
+/// use microbit::microphone::Spectrum;
+///
+/// // 1kHz sine of amplitude 500 around 2048, sampled at 16kHz
+/// let samples: [i16; 256] = core::array::from_fn(|i| {
+///     let t = i as f64 / 16_000.0;
+///     (2048.0 + 500.0 * (2.0 * core::f64::consts::PI * 1000.0 * t).sin()) as i16
+/// });
+///
+/// let mut spectrum = Spectrum::<256>::new(16_000);
+/// spectrum.analyse(&samples);
+///
+/// assert_eq!(spectrum.bin_hz(16), 1000);
+/// assert!((480..=520).contains(&spectrum.magnitude(16)));
+/// assert!(spectrum.magnitude(40) < 5);
+///
+/// let dominant = spectrum.dominant_frequency().unwrap();
+/// assert!((990..=1010).contains(&dominant));
+/// ```
+#[derive(Clone, Debug)]
+pub struct Spectrum<const N: usize> {
+    sample_rate_hz: u32,
+    // after the analysis, `re` holds the magnitudes of the first N / 2 bins
+    re: [i32; N],
+    im: [i32; N],
+}
+
+impl<const N: usize> Spectrum<N> {
+    /// Create an empty spectrum for samples taken at `sample_rate_hz`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `N` is not a power of two from 2 to [`MAX_SPECTRUM_LEN`].
+    pub const fn new(sample_rate_hz: u32) -> Self {
+        assert!(
+            N >= 2 && N <= MAX_SPECTRUM_LEN && N.is_power_of_two(),
+            "unsupported spectrum length"
+        );
+        Self {
+            sample_rate_hz,
+            re: [0; N],
+            im: [0; N],
+        }
+    }
+
+    /// Returns the sample rate, in Hz.
+    pub fn sample_rate_hz(&self) -> u32 {
+        self.sample_rate_hz
+    }
+
+    /// Compute the spectrum of `samples`, replacing the previous one.
+    pub fn analyse(&mut self, samples: &[i16; N]) {
+        let sum: i64 = samples.iter().map(|&sample| i64::from(sample)).sum();
+        let mean = (sum / N as i64) as i32;
+        let step = phase_step(N);
+        for (i, &sample) in samples.iter().enumerate() {
+            let phase = (i as u32).wrapping_mul(step);
+            let window = i64::from((32_767 - cosine(phase)) / 2);
+            // scaled up 16 times, which still fits for N samples of 16 bits
+            self.re[i] = ((i64::from(i32::from(sample) - mean) * window) >> 11) as i32;
+            self.im[i] = 0;
+        }
+
+        fft(&mut self.re, &mut self.im);
+
+        // undo the scaling, the window and the FFT gain
+        let scale = 4 * N as u64;
+        for bin in 0..N / 2 {
+            let re = i64::from(self.re[bin]);
+            let im = i64::from(self.im[bin]);
+            let magnitude = isqrt((re * re + im * im) as u64);
+            self.re[bin] = (magnitude / scale) as i32;
+        }
+    }
+
+    /// Returns the frequency of `bin`, in Hz.
+    pub fn bin_hz(&self, bin: usize) -> u32 {
+        (bin as u64 * u64::from(self.sample_rate_hz) / N as u64) as u32
+    }
+
+    /// Returns the magnitude of `bin`, `0` past the first `N / 2` bins.
+    pub fn magnitude(&self, bin: usize) -> u32 {
+        if bin < N / 2 {
+            self.re[bin] as u32
+        } else {
+            0
+        }
+    }
+
+    /// Returns the magnitudes of the first `N / 2` bins.
+    pub fn magnitudes(&self) -> impl Iterator<Item = u32> + '_ {
+        self.re[..N / 2].iter().map(|&magnitude| magnitude as u32)
+    }
+
+    /// Returns the frequency of the loudest bin, in Hz, refined between
+    /// bins from the magnitudes of its neighbours.
+    ///
+    /// The constant component is ignored. Returns `None` for silence.
+    pub fn dominant_frequency(&self) -> Option<u32> {
+        let (bin, peak) = (1..N / 2)
+            .map(|bin| (bin, self.magnitude(bin)))
+            .max_by_key(|&(_, magnitude)| magnitude)?;
+        if peak == 0 {
+            return None;
+        }
+
+        // the Hann window spreads a sine over three bins, in known ratios
+        let left = i64::from(self.magnitude(bin - 1));
+        let right = i64::from(self.magnitude(bin + 1));
+        let num = 2 * (right - left);
+        let den = left + 2 * i64::from(peak) + right;
+        let hz = (bin as i64 * den + num) * i64::from(self.sample_rate_hz) / (den * N as i64);
+        Some(hz.max(0) as u32)
+    }
+
+    /// Returns the energy of the bins from `low_hz` included to `high_hz`
+    /// excluded, as a mean square in ADC counts.
+    pub fn band_energy(&self, low_hz: u32, high_hz: u32) -> u64 {
+        let sum: u64 = (1..N / 2)
+            .filter(|&bin| (low_hz..high_hz).contains(&self.bin_hz(bin)))
+            .map(|bin| u64::from(self.magnitude(bin)).pow(2))
+            .sum();
+        // a sine of amplitude A has a mean square of A² / 2 and the Hann
+        // window spreads it over bins adding up to 1.5 A²
+        sum / 3
+    }
+
+    /// Returns the levels of [`BANDS`] bands an octave wide, lowest first,
+    /// on the same scale as [`LevelMeter`](super::LevelMeter).
+    ///
+    /// The highest band goes from a quarter to half the sample rate, the
+    /// lowest one takes all the frequencies below its upper edge.
+    pub fn bands(&self) -> [u8; BANDS] {
+        let nyquist_hz = self.sample_rate_hz / 2;
+        let mut low_hz = 1;
+        core::array::from_fn(|band| {
+            let high_hz = nyquist_hz >> (BANDS - 1 - band);
+            let energy = self.band_energy(low_hz, high_hz);
+            low_hz = high_hz;
+            level(energy)
+        })
+    }
+}
+
+/// Measures the amplitude of a single frequency with the Goertzel algorithm.
+///
+/// This is cheaper than a full [`Spectrum`] when only a few frequencies
+/// matter, like detecting a whistle or a DTMF tone. The amplitude is in ADC
+/// counts; it is exact when the block holds a whole number of periods and
+/// lower otherwise.
+///
+/// ```
+/// # use microbit_common as microbit;
+/// use microbit::microphone::Goertzel;
+///
+/// // 1kHz sine of amplitude 300, sampled at 8kHz
+/// let samples: Vec<i16> = (0..400)
+///     .map(|i| (300.0 * (2.0 * core::f64::consts::PI * i as f64 / 8.0).sin()) as i16)
+///     .collect();
+///
+/// let amplitude = Goertzel::new(1000, 8000).amplitude(&samples);
+/// assert!((290..=310).contains(&amplitude));
+/// assert!(Goertzel::new(1500, 8000).amplitude(&samples) < 10);
+/// ```
+#[derive(Copy, Clone, Debug)]
+pub struct Goertzel {
+    // 2 cos(2π f / fs) with 14 fractional bits
+    coefficient: i64,
+}
+
+impl Goertzel {
+    /// Create a detector for `target_hz` in samples taken at
+    /// `sample_rate_hz`.
+    pub fn new(target_hz: u32, sample_rate_hz: u32) -> Self {
+        let phase = ((u64::from(target_hz) << 32) / u64::from(sample_rate_hz.max(1))) as u32;
+        Self {
+            // a cosine with 15 fractional bits is twice it with 14
+            coefficient: i64::from(cosine(phase)),
+        }
+    }
+
+    /// Returns the amplitude of the target frequency in `samples`, `0` for
+    /// no samples.
+    pub fn amplitude(&self, samples: &[i16]) -> u32 {
+        if samples.is_empty() {
+            return 0;
+        }
+        let sum: i64 = samples.iter().map(|&sample| i64::from(sample)).sum();
+        let mean = sum / samples.len() as i64;
+
+        let (mut s1, mut s2) = (0i64, 0i64);
+        for &sample in samples {
+            let s0 = i64::from(sample) - mean + ((self.coefficient * s1) >> 14) - s2;
+            s2 = s1;
+            s1 = s0;
+        }
+        let power = s1 * s1 + s2 * s2 - ((self.coefficient * s1) >> 14) * s2;
+        (2 * isqrt(power.max(0) as u64) / samples.len() as u64) as u32
+    }
+}
+
+/// Draw band levels as a bar graph on the 5×5 display, lowest band on the
+/// left.
+///
+/// Each column lights up from the bottom in proportion to its level, the top
+/// LED of a bar dimmer when partly reached. The image works both with the
+/// blocking display, where any lit LED is fully on, and as a
+/// `GreyscaleImage` for the non-blocking one.
+///
+/// ```
+/// # use microbit_common as microbit;
+/// use microbit::microphone::bar_graph;
+///
+/// let image = bar_graph(&[255, 153, 0, 51, 30]);
+/// assert_eq!(
+///     image,
+///     [
+///         [9, 0, 0, 0, 0],
+///         [9, 0, 0, 0, 0],
+///         [9, 9, 0, 0, 0],
+///         [9, 9, 0, 0, 0],
+///         [9, 9, 0, 9, 5],
+///     ]
+/// );
+/// ```
+pub fn bar_graph(levels: &[u8; BANDS]) -> [[u8; 5]; 5] {
+    let mut image = [[0; 5]; 5];
+    for (x, &level) in levels.iter().enumerate() {
+        let mut remaining = u32::from(level) * 5 * MAX_BRIGHTNESS / 255;
+        for row in image.iter_mut().rev() {
+            let brightness = remaining.min(MAX_BRIGHTNESS);
+            row[x] = brightness as u8;
+            remaining -= brightness;
+        }
+    }
+    image
+}
+
+/// In place radix-2 FFT of `re` and `im`, which have the same power of two
+/// length.
+fn fft(re: &mut [i32], im: &mut [i32]) {
+    let n = re.len();
+    let bits = n.trailing_zeros();
+    for i in 0..n {
+        let j = i.reverse_bits() >> (usize::BITS - bits);
+        if i < j {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+
+    let mut len = 2;
+    while len <= n {
+        let half = len / 2;
+        let step = phase_step(len);
+        for start in (0..n).step_by(len) {
+            for k in 0..half {
+                let phase = (k as u32).wrapping_mul(step);
+                let (cos, sin) = (i64::from(cosine(phase)), i64::from(sine(phase)));
+                let (a, b) = (start + k, start + k + half);
+                // multiply by the twiddle factor e^(-i phase)
+                let (re_b, im_b) = (i64::from(re[b]), i64::from(im[b]));
+                let t_re = ((re_b * cos + im_b * sin) >> 15) as i32;
+                let t_im = ((im_b * cos - re_b * sin) >> 15) as i32;
+                re[b] = re[a] - t_re;
+                im[b] = im[a] - t_im;
+                re[a] += t_re;
+                im[a] += t_im;
+            }
+        }
+        len *= 2;
+    }
+}
+
+/// Phase step of one sample in a period of `len` samples, where a full
+/// period is `1 << 32`.
+fn phase_step(len: usize) -> u32 {
+    ((1u64 << 32) / len as u64) as u32
+}
+
+/// Cosine of a phase where a full period is `1 << 32`, from `-32767` to
+/// `32767`.
+fn cosine(phase: u32) -> i32 {
+    sine(phase.wrapping_add(1 << 30))
+}
+
+/// Integer square root, rounded down.
+fn isqrt(x: u64) -> u64 {
+    if x == 0 {
+        return 0;
+    }
+    // start at or above the root and come down
+    let mut root = 1u64 << (64 - x.leading_zeros()).div_ceil(2);
+    loop {
+        let next = (root + x / root) / 2;
+        if next >= root {
+            return root;
+        }
+        root = next;
+    }
+}
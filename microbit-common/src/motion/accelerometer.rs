@@ -0,0 +1,433 @@
+//! Accelerometer driver for all the motion sensors fitted to micro:bits.
+
+use super::Vector;
+use crate::sensors::Celsius;
+use core::fmt;
+use embedded_hal::i2c::I2c;
+
+/// The accelerometers found on micro:bits.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Model {
+    /// NXP MMA8653FC, on early micro:bit V1 boards, next to a MAG3110
+    /// magnetometer.
+    Mma8653,
+    /// NXP FXOS8700CQ accelerometer and magnetometer, on some micro:bit
+    /// V1.5 boards.
+    Fxos8700,
+    /// ST LSM303AGR accelerometer and magnetometer, on the other micro:bit
+    /// V1.5 boards and on the V2.
+    Lsm303agr,
+}
+
+impl Model {
+    /// Every model, in the order they are probed.
+    const ALL: [Model; 3] = [Model::Lsm303agr, Model::Fxos8700, Model::Mma8653];
+
+    /// 7-bit I2C address of the accelerometer.
+    pub const fn address(self) -> u8 {
+        match self {
+            Model::Mma8653 => 0x1D,
+            Model::Fxos8700 => 0x1E,
+            Model::Lsm303agr => 0x19,
+        }
+    }
+
+    /// Register holding the identity of the chip, and its expected value.
+    const fn who_am_i(self) -> (u8, u8) {
+        match self {
+            Model::Mma8653 => (0x0D, 0x5A),
+            Model::Fxos8700 => (0x0D, 0xC7),
+            Model::Lsm303agr => (0x0F, 0x33),
+        }
+    }
+
+    /// Number of significant bits of the acceleration values.
+    const fn resolution_bits(self) -> u32 {
+        match self {
+            Model::Mma8653 => 10,
+            Model::Fxos8700 => 14,
+            // in high resolution mode
+            Model::Lsm303agr => 12,
+        }
+    }
+
+    /// Turn the sensor axes into the board ones, which depend on how the
+    /// chip is placed on the board.
+    const fn orient(self, [x, y, z]: [i32; 3]) -> Vector {
+        match self {
+            Model::Mma8653 | Model::Lsm303agr => Vector::new(x, -y, z),
+            Model::Fxos8700 => Vector::new(-y, -x, z),
+        }
+    }
+}
+
+/// Acceleration ranges, in g, supported by all the models.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Range {
+    /// ±2g, the most precise.
+    G2,
+    /// ±4g.
+    G4,
+    /// ±8g.
+    G8,
+}
+
+impl Range {
+    /// Returns the largest acceleration measured, in g.
+    pub const fn g(self) -> i32 {
+        match self {
+            Range::G2 => 2,
+            Range::G4 => 4,
+            Range::G8 => 8,
+        }
+    }
+
+    /// Value of the full scale bits of the range, the same on all models.
+    const fn bits(self) -> u8 {
+        match self {
+            Range::G2 => 0b00,
+            Range::G4 => 0b01,
+            Range::G8 => 0b10,
+        }
+    }
+}
+
+/// Events the accelerometer can signal on its interrupt line.
+///
+/// The line is the [`INT_ACC`](crate::gpio::INT_ACC) pin, held low while an
+/// event is pending.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Interrupt {
+    /// A new sample is ready, 50 times a second. Reading the acceleration
+    /// clears it.
+    DataReady,
+    /// The acceleration along an axis went past `threshold_mg` either way,
+    /// gravity included: a threshold above 1000 mg ignores the board lying
+    /// still. [`Accelerometer::clear_interrupt`] clears it.
+    Motion {
+        /// Acceleration to exceed, in milli-g.
+        threshold_mg: u16,
+    },
+}
+
+/// Errors of the motion sensors.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Error<E> {
+    /// None of the known sensors answered on the bus.
+    NotFound,
+    /// The I2C transfer failed.
+    I2c(E),
+}
+
+impl<E> From<E> for Error<E> {
+    fn from(error: E) -> Self {
+        Error::I2c(error)
+    }
+}
+
+impl<E: fmt::Debug> fmt::Display for Error<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NotFound => f.write_str("motion sensor not found"),
+            Error::I2c(error) => write!(f, "I2C error: {:?}", error),
+        }
+    }
+}
+
+// MMA8653 and FXOS8700 registers
+const NXP_OUT_X_MSB: u8 = 0x01;
+const NXP_XYZ_DATA_CFG: u8 = 0x0E;
+const NXP_FF_MT_CFG: u8 = 0x15;
+const NXP_FF_MT_SRC: u8 = 0x16;
+const NXP_FF_MT_THS: u8 = 0x17;
+pub(super) const NXP_CTRL_REG1: u8 = 0x2A;
+// 50Hz and active
+const NXP_CTRL_REG1_ACTIVE_50HZ: u8 = (0b100 << 3) | 1;
+const NXP_CTRL_REG4: u8 = 0x2D;
+const NXP_CTRL_REG5: u8 = 0x2E;
+// data ready and motion interrupt bits, in CTRL_REG4 to enable them and in
+// CTRL_REG5 to route them to INT1
+const NXP_INT_DRDY: u8 = 1 << 0;
+const NXP_INT_FF_MT: u8 = 1 << 2;
+// latched motion detection on all axes
+const NXP_FF_MT_CFG_MOTION: u8 = (1 << 7) | (1 << 6) | (0b111 << 3);
+// milli-g per step of the motion threshold
+const NXP_FF_MT_THS_MG: u16 = 63;
+
+// FXOS8700 registers
+const FXOS_TEMP: u8 = 0x51;
+pub(super) const FXOS_M_CTRL_REG1: u8 = 0x5B;
+// sensors enabled, only the accelerometer when both bits are clear
+const FXOS_M_CTRL_REG1_HMS: u8 = 0b11;
+
+// LSM303AGR registers
+const LSM_OUT_TEMP_L_A: u8 = 0x0C;
+const LSM_TEMP_CFG_REG_A: u8 = 0x1F;
+const LSM_CTRL_REG1_A: u8 = 0x20;
+const LSM_CTRL_REG3_A: u8 = 0x22;
+const LSM_CTRL_REG4_A: u8 = 0x23;
+const LSM_CTRL_REG5_A: u8 = 0x24;
+const LSM_CTRL_REG6_A: u8 = 0x25;
+const LSM_OUT_X_L_A: u8 = 0x28;
+// 50Hz with all axes enabled
+const LSM_CTRL_REG1_A_50HZ: u8 = (0b0100 << 4) | 0b111;
+// block data update and high resolution
+const LSM_CTRL_REG4_A_BDU_HR: u8 = (1 << 7) | (1 << 3);
+const LSM_INT1_CFG_A: u8 = 0x30;
+const LSM_INT1_SRC_A: u8 = 0x31;
+const LSM_INT1_THS_A: u8 = 0x32;
+// data ready and motion interrupts on INT1
+const LSM_CTRL_REG3_A_I1_DRDY1: u8 = 1 << 4;
+const LSM_CTRL_REG3_A_I1_AOI1: u8 = 1 << 6;
+// INT1 latched until INT1_SRC_A is read
+const LSM_CTRL_REG5_A_LIR_INT1: u8 = 1 << 3;
+// temperature sensor enabled
+const LSM_TEMP_CFG_REG_A_TEMP_EN: u8 = 0b11 << 6;
+// interrupts active low
+const LSM_CTRL_REG6_A_INT_POLARITY: u8 = 1 << 1;
+// high events on any axis
+const LSM_INT1_CFG_A_HIGH: u8 = (1 << 1) | (1 << 3) | (1 << 5);
+// set on a register address to read several registers in a row
+const LSM_AUTO_INCREMENT: u8 = 0x80;
+
+/// The accelerometer of the micro:bit, whichever model it is.
+///
+/// The sensor is sampled at 50Hz and the acceleration is returned in
+/// milli-g, along the axes described in [`Vector`].
+///
+/// `I2C` is the internal I2C bus: a `Twi` on the micro:bit V1, a `Twim` on
+/// the V2, or anything implementing [`I2c`].
+pub struct Accelerometer<I2C> {
+    i2c: I2C,
+    model: Model,
+    range: Range,
+}
+
+impl<I2C: I2c> Accelerometer<I2C> {
+    /// Find the accelerometer on the bus and start it in the ±2g range.
+    pub fn new(mut i2c: I2C) -> Result<Self, Error<I2C::Error>> {
+        let model = Self::probe(&mut i2c).ok_or(Error::NotFound)?;
+        let mut accelerometer = Self {
+            i2c,
+            model,
+            range: Range::G2,
+        };
+        accelerometer.configure()?;
+        Ok(accelerometer)
+    }
+
+    /// Returns the accelerometer model found on the bus, if any.
+    pub fn probe(i2c: &mut I2C) -> Option<Model> {
+        Model::ALL.iter().copied().find(|&model| {
+            let (register, id) = model.who_am_i();
+            let mut value = [0];
+            i2c.write_read(model.address(), &[register], &mut value)
+                .is_ok()
+                && value[0] == id
+        })
+    }
+
+    /// Returns the model of the accelerometer.
+    pub fn model(&self) -> Model {
+        self.model
+    }
+
+    /// Returns the acceleration range.
+    pub fn range(&self) -> Range {
+        self.range
+    }
+
+    /// Set the acceleration range.
+    ///
+    /// Wider ranges measure stronger movements, with less precision.
+    pub fn set_range(&mut self, range: Range) -> Result<(), Error<I2C::Error>> {
+        self.range = range;
+        self.configure()
+    }
+
+    /// Returns the latest acceleration, in milli-g.
+    pub fn acceleration(&mut self) -> Result<Vector, Error<I2C::Error>> {
+        let address = self.model.address();
+        let mut data = [0; 6];
+        let raw = match self.model {
+            Model::Mma8653 | Model::Fxos8700 => {
+                self.i2c.write_read(address, &[NXP_OUT_X_MSB], &mut data)?;
+                [0, 2, 4].map(|i| i16::from_be_bytes([data[i], data[i + 1]]))
+            }
+            Model::Lsm303agr => {
+                self.i2c
+                    .write_read(address, &[LSM_OUT_X_L_A | LSM_AUTO_INCREMENT], &mut data)?;
+                [0, 2, 4].map(|i| i16::from_le_bytes([data[i], data[i + 1]]))
+            }
+        };
+
+        // values are left aligned, and full scale is the range
+        let bits = self.model.resolution_bits();
+        let milli_g = raw
+            .map(|value| ((i32::from(value) >> (16 - bits)) * self.range.g() * 1000) >> (bits - 1));
+        Ok(self.model.orient(milli_g))
+    }
+
+    /// Returns the temperature of the sensor, if it has a temperature
+    /// sensor: the LSM303AGR has one, and so does the FXOS8700, which only
+    /// measures it while its magnetometer runs, as a
+    /// [`Compass`](super::Compass) makes it. The MMA8653 has none.
+    ///
+    /// See [`Temperature`](crate::sensors::Temperature) to apply a
+    /// calibration offset.
+    pub fn temperature(&mut self) -> Result<Option<Celsius>, Error<I2C::Error>> {
+        match self.model {
+            Model::Mma8653 => Ok(None),
+            Model::Fxos8700 => {
+                let address = self.model.address();
+                let mut mode = [0];
+                self.i2c
+                    .write_read(address, &[FXOS_M_CTRL_REG1], &mut mode)?;
+                if mode[0] & FXOS_M_CTRL_REG1_HMS == 0 {
+                    return Ok(None);
+                }
+                let mut data = [0];
+                self.i2c.write_read(address, &[FXOS_TEMP], &mut data)?;
+                // a signed byte, 0.96°C per step
+                Ok(Some(Celsius::from_centi(i32::from(data[0] as i8) * 96)))
+            }
+            Model::Lsm303agr => {
+                let mut data = [0; 2];
+                self.i2c.write_read(
+                    self.model.address(),
+                    &[LSM_OUT_TEMP_L_A | LSM_AUTO_INCREMENT],
+                    &mut data,
+                )?;
+                // left aligned, a degree per 256, and 0 at 25°C
+                let raw = i32::from(i16::from_le_bytes(data));
+                Ok(Some(Celsius::from_centi(2500 + raw * 100 / 256)))
+            }
+        }
+    }
+
+    /// Signal `interrupt` on the interrupt line, instead of any other event.
+    ///
+    /// The motion threshold is set for the current range: enable the
+    /// interrupt again after [`set_range`](Self::set_range). See
+    /// [`InterruptAccelerometer`](super::InterruptAccelerometer) to be woken
+    /// up by the interrupt.
+    pub fn enable_interrupt(&mut self, interrupt: Interrupt) -> Result<(), Error<I2C::Error>> {
+        let address = self.model.address();
+        match self.model {
+            Model::Mma8653 | Model::Fxos8700 => {
+                let (enable, threshold) = match interrupt {
+                    Interrupt::DataReady => (NXP_INT_DRDY, None),
+                    Interrupt::Motion { threshold_mg } => {
+                        let steps = (threshold_mg / NXP_FF_MT_THS_MG).min(0x7F) as u8;
+                        (NXP_INT_FF_MT, Some(steps))
+                    }
+                };
+                self.set_nxp_interrupts(enable, threshold)
+            }
+            Model::Lsm303agr => {
+                let (enable, latch) = match interrupt {
+                    Interrupt::DataReady => (LSM_CTRL_REG3_A_I1_DRDY1, 0),
+                    Interrupt::Motion { threshold_mg } => {
+                        // the threshold steps are 1/128th of the range
+                        let step = (self.range.g() * 1000 / 128) as u16;
+                        let steps = (threshold_mg / step).min(0x7F) as u8;
+                        self.i2c
+                            .write(address, &[LSM_INT1_CFG_A, LSM_INT1_CFG_A_HIGH])?;
+                        self.i2c.write(address, &[LSM_INT1_THS_A, steps])?;
+                        // latch the motion, so the line stays low until
+                        // cleared, however short the motion
+                        (LSM_CTRL_REG3_A_I1_AOI1, LSM_CTRL_REG5_A_LIR_INT1)
+                    }
+                };
+                self.i2c.write(address, &[LSM_CTRL_REG5_A, latch])?;
+                self.i2c
+                    .write(address, &[LSM_CTRL_REG6_A, LSM_CTRL_REG6_A_INT_POLARITY])?;
+                self.i2c.write(address, &[LSM_CTRL_REG3_A, enable])?;
+                Ok(())
+            }
+        }
+    }
+
+    /// Stop signalling events on the interrupt line.
+    pub fn disable_interrupt(&mut self) -> Result<(), Error<I2C::Error>> {
+        match self.model {
+            Model::Mma8653 | Model::Fxos8700 => self.set_nxp_interrupts(0, None),
+            Model::Lsm303agr => {
+                let address = self.model.address();
+                self.i2c.write(address, &[LSM_CTRL_REG3_A, 0])?;
+                self.i2c.write(address, &[LSM_CTRL_REG5_A, 0])?;
+                Ok(())
+            }
+        }
+    }
+
+    /// Clear a pending [`Interrupt::Motion`], releasing the interrupt line.
+    pub fn clear_interrupt(&mut self) -> Result<(), Error<I2C::Error>> {
+        let register = match self.model {
+            Model::Mma8653 | Model::Fxos8700 => NXP_FF_MT_SRC,
+            Model::Lsm303agr => LSM_INT1_SRC_A,
+        };
+        let mut source = [0];
+        self.i2c
+            .write_read(self.model.address(), &[register], &mut source)?;
+        Ok(())
+    }
+
+    /// Give back the I2C bus, leaving the accelerometer running.
+    pub fn free(self) -> I2C {
+        self.i2c
+    }
+
+    /// Returns the I2C bus, to talk to the other sensors on it.
+    pub(super) fn i2c(&mut self) -> &mut I2C {
+        &mut self.i2c
+    }
+
+    /// Enable the NXP interrupts in `enable` on INT1, with the motion
+    /// `threshold` if given.
+    fn set_nxp_interrupts(
+        &mut self,
+        enable: u8,
+        threshold: Option<u8>,
+    ) -> Result<(), Error<I2C::Error>> {
+        let address = self.model.address();
+        // the configuration can only change in standby
+        self.i2c.write(address, &[NXP_CTRL_REG1, 0])?;
+        if let Some(threshold) = threshold {
+            self.i2c
+                .write(address, &[NXP_FF_MT_CFG, NXP_FF_MT_CFG_MOTION])?;
+            self.i2c.write(address, &[NXP_FF_MT_THS, threshold])?;
+        }
+        self.i2c.write(address, &[NXP_CTRL_REG4, enable])?;
+        self.i2c.write(address, &[NXP_CTRL_REG5, enable])?;
+        self.i2c
+            .write(address, &[NXP_CTRL_REG1, NXP_CTRL_REG1_ACTIVE_50HZ])?;
+        Ok(())
+    }
+
+    /// Set the sensor up for the current range.
+    pub(super) fn configure(&mut self) -> Result<(), Error<I2C::Error>> {
+        let address = self.model.address();
+        let range = self.range.bits();
+        match self.model {
+            Model::Mma8653 | Model::Fxos8700 => {
+                // the configuration can only change in standby
+                self.i2c.write(address, &[NXP_CTRL_REG1, 0])?;
+                self.i2c.write(address, &[NXP_XYZ_DATA_CFG, range])?;
+                self.i2c
+                    .write(address, &[NXP_CTRL_REG1, NXP_CTRL_REG1_ACTIVE_50HZ])?;
+            }
+            Model::Lsm303agr => {
+                self.i2c
+                    .write(address, &[LSM_CTRL_REG1_A, LSM_CTRL_REG1_A_50HZ])?;
+                self.i2c.write(
+                    address,
+                    &[LSM_CTRL_REG4_A, LSM_CTRL_REG4_A_BDU_HR | (range << 4)],
+                )?;
+                self.i2c
+                    .write(address, &[LSM_TEMP_CFG_REG_A, LSM_TEMP_CFG_REG_A_TEMP_EN])?;
+            }
+        }
+        Ok(())
+    }
+}
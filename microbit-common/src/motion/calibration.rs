@@ -0,0 +1,328 @@
+//! Compass calibration, and its storage in flash.
+
+use super::Vector;
+use embedded_storage::nor_flash::{NorFlash, ReadNorFlash};
+
+/// Scale factor leaving the field unchanged, see [`Calibration::new`].
+pub const UNIT_SCALE: i32 = 1024;
+
+/// Length of a calibration record in flash, in bytes.
+pub const RECORD_LEN: usize = 32;
+
+/// Marks a calibration record.
+const MAGIC: u32 = 0x5350_4D43;
+
+/// Tilt in milli-g between two columns or rows of the calibration screen.
+const TILT_STEP: i32 = 300;
+
+/// Number of samples the cursor stays on, then off.
+const BLINK_SAMPLES: u32 = 4;
+
+/// Corrections for the distortions of the magnetic field by the board.
+///
+/// Magnets and iron near the magnetometer add their own field to the
+/// earth's, shifting all the readings by an offset, and the sensor can be
+/// more sensitive along one axis than another. A calibration removes the
+/// offset and scales the axes back to the same sensitivity.
+///
+/// A calibration is measured with a [`Calibrator`], and can be saved to
+/// flash so it survives resets. Only the hal of the V2 drives the NVMC as a
+/// [`NorFlash`], so on the V1 the calibration has to be measured again
+/// after each reset:
+///
+/// ```no_run
+/// # use microbit_common as microbit;
+/// # #[cfg(feature = "v2")]
+/// # {
+/// use microbit::{hal::nvmc::Nvmc, motion::Calibration, Board};
+///
+/// // the last page of the flash of the V2, not used by the program
+/// const PAGE: usize = 0x7F000;
+/// const PAGE_LEN: usize = 4096;
+///
+/// let board = Board::take().unwrap();
+/// // Safety: nothing else uses that page
+/// let page = unsafe { core::slice::from_raw_parts_mut(PAGE as *mut u8, PAGE_LEN) };
+/// let mut flash = Nvmc::new(board.NVMC, page);
+///
+/// let calibration = match Calibration::load(&mut flash, 0).unwrap() {
+///     Some(calibration) => calibration,
+///     None => {
+///         // calibrate, then
+///         # let calibration = Calibration::default();
+///         calibration.save(&mut flash, 0).unwrap();
+///         calibration
+///     }
+/// };
+/// # }
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Calibration {
+    offset: Vector,
+    scale: Vector,
+}
+
+impl Calibration {
+    /// Create a calibration removing `offset`, in nT, then multiplying each
+    /// axis by its `scale`, in 1/[`UNIT_SCALE`]ths.
+    pub const fn new(offset: Vector, scale: Vector) -> Self {
+        Self { offset, scale }
+    }
+
+    /// Returns the offset removed from the field, in nT.
+    pub fn offset(&self) -> Vector {
+        self.offset
+    }
+
+    /// Returns the scale factors of the axes, in 1/[`UNIT_SCALE`]ths.
+    pub fn scale(&self) -> Vector {
+        self.scale
+    }
+
+    /// Returns the corrected `field`.
+    pub fn apply(&self, field: Vector) -> Vector {
+        let correct = |value: i32, offset: i32, scale: i32| {
+            (i64::from(value - offset) * i64::from(scale) / i64::from(UNIT_SCALE)) as i32
+        };
+        Vector::new(
+            correct(field.x, self.offset.x, self.scale.x),
+            correct(field.y, self.offset.y, self.scale.y),
+            correct(field.z, self.offset.z, self.scale.z),
+        )
+    }
+
+    /// Returns the calibration as a record to store.
+    ///
+    /// The record holds a marker and a checksum, so reading back erased or
+    /// corrupted flash gives no calibration:
+    ///
+    /// ```
+    /// # use microbit_common as microbit;
+    /// use microbit::motion::{Calibration, Vector, RECORD_LEN};
+    ///
+    /// let calibration = Calibration::new(Vector::new(-1200, 300, 4500), Vector::new(1000, 1050, 980));
+    /// let mut record = calibration.to_bytes();
+    /// assert_eq!(Calibration::from_bytes(&record), Some(calibration));
+    ///
+    /// record[5] ^= 1;
+    /// assert_eq!(Calibration::from_bytes(&record), None);
+    /// assert_eq!(Calibration::from_bytes(&[0xFF; RECORD_LEN]), None);
+    /// ```
+    pub fn to_bytes(&self) -> [u8; RECORD_LEN] {
+        let mut words = [
+            MAGIC,
+            self.offset.x as u32,
+            self.offset.y as u32,
+            self.offset.z as u32,
+            self.scale.x as u32,
+            self.scale.y as u32,
+            self.scale.z as u32,
+            0,
+        ];
+        words[7] = checksum(&words[..7]);
+
+        let mut bytes = [0; RECORD_LEN];
+        for (chunk, word) in bytes.chunks_exact_mut(4).zip(words) {
+            chunk.copy_from_slice(&word.to_le_bytes());
+        }
+        bytes
+    }
+
+    /// Returns the calibration stored in `bytes`, if it is a valid record.
+    pub fn from_bytes(bytes: &[u8; RECORD_LEN]) -> Option<Self> {
+        let mut words = [0; RECORD_LEN / 4];
+        for (word, chunk) in words.iter_mut().zip(bytes.chunks_exact(4)) {
+            *word = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        }
+        if words[0] != MAGIC || words[7] != checksum(&words[..7]) {
+            return None;
+        }
+        let value = |index: usize| words[index] as i32;
+        Some(Self::new(
+            Vector::new(value(1), value(2), value(3)),
+            Vector::new(value(4), value(5), value(6)),
+        ))
+    }
+
+    /// Save the calibration at `offset` in `flash`, like the NVMC of the V2.
+    ///
+    /// The whole erase page starting at `offset` is erased first, so it
+    /// must be aligned to a page and not hold anything else.
+    pub fn save<F: NorFlash>(&self, flash: &mut F, offset: u32) -> Result<(), F::Error> {
+        flash.erase(offset, offset + F::ERASE_SIZE as u32)?;
+        flash.write(offset, &self.to_bytes())
+    }
+
+    /// Returns the calibration saved at `offset` in `flash`, if any.
+    pub fn load<F: ReadNorFlash>(flash: &mut F, offset: u32) -> Result<Option<Self>, F::Error> {
+        let mut bytes = [0; RECORD_LEN];
+        flash.read(offset, &mut bytes)?;
+        Ok(Self::from_bytes(&bytes))
+    }
+}
+
+impl Default for Calibration {
+    /// A calibration leaving the field unchanged.
+    fn default() -> Self {
+        Self::new(
+            Vector::default(),
+            Vector::new(UNIT_SCALE, UNIT_SCALE, UNIT_SCALE),
+        )
+    }
+}
+
+fn checksum(words: &[u32]) -> u32 {
+    words
+        .iter()
+        .fold(0, |sum: u32, &word| sum.rotate_left(5) ^ word)
+}
+
+/// The "fill the screen" calibration game of the micro:bit firmware.
+///
+/// Tilting the board rolls a blinking dot around the display, lighting each
+/// LED it goes over. Meanwhile, the calibrator records the magnetic field,
+/// which points every which way as the board is turned around. Once the
+/// screen is full, the largest and smallest field along each axis give the
+/// [`Calibration`]: the offset is halfway between them, and the scales even
+/// out their spreads.
+///
+/// [`Compass::calibrate`](super::Compass::calibrate) plays the game on the
+/// display, but the calibrator itself doesn't touch any hardware:
+///
+/// ```
+/// # use microbit_common as microbit;
+/// use microbit::motion::{Calibrator, Vector};
+///
+/// // a field of 40000nT seen through an offset
+/// let offset = Vector::new(3000, -1500, 500);
+/// let directions = [(1, 0, 0), (-1, 0, 0), (0, 1, 0), (0, -1, 0), (0, 0, 1), (0, 0, -1)];
+///
+/// let mut calibrator = Calibrator::new();
+/// for (i, (x, y, z)) in directions.iter().cycle().take(25).enumerate() {
+///     // tilted towards each LED in turn
+///     let (column, row) = (i as i32 % 5, i as i32 / 5);
+///     let acceleration = Vector::new(column * 300 - 600, 600 - row * 300, -800);
+///     let field = Vector::new(x * 40_000 + offset.x, y * 40_000 + offset.y, z * 40_000 + offset.z);
+///     assert!(calibrator.calibration().is_none());
+///     calibrator.update(acceleration, field);
+/// }
+///
+/// let calibration = calibrator.calibration().unwrap();
+/// assert_eq!(calibration.offset(), offset);
+/// let field = Vector::new(40_000 + offset.x, offset.y, offset.z);
+/// assert_eq!(calibration.apply(field), Vector::new(40_000, 0, 0));
+/// ```
+#[derive(Copy, Clone, Debug)]
+pub struct Calibrator {
+    // one bit per LED, row by row
+    lit: u32,
+    cursor: (usize, usize),
+    samples: u32,
+    min: Vector,
+    max: Vector,
+}
+
+impl Calibrator {
+    /// Create a calibrator with a blank screen.
+    pub const fn new() -> Self {
+        Self {
+            lit: 0,
+            cursor: (2, 2),
+            samples: 0,
+            min: Vector::new(0, 0, 0),
+            max: Vector::new(0, 0, 0),
+        }
+    }
+
+    /// Feed the next acceleration, in milli-g, and raw magnetic field, in
+    /// nT.
+    pub fn update(&mut self, acceleration: Vector, field: Vector) {
+        if self.samples == 0 {
+            self.min = field;
+            self.max = field;
+        }
+        self.min = Vector::new(
+            self.min.x.min(field.x),
+            self.min.y.min(field.y),
+            self.min.z.min(field.z),
+        );
+        self.max = Vector::new(
+            self.max.x.max(field.x),
+            self.max.y.max(field.y),
+            self.max.z.max(field.z),
+        );
+        self.samples = self.samples.wrapping_add(1);
+
+        // the dot rolls down the slope
+        let column = tilt_step(acceleration.x);
+        let row = 4 - tilt_step(acceleration.y);
+        self.cursor = (column, row);
+        self.lit |= 1 << (row * 5 + column);
+    }
+
+    /// Returns `true` once every LED is lit.
+    pub fn is_complete(&self) -> bool {
+        self.lit == (1 << 25) - 1
+    }
+
+    /// Returns the screen to show, with the LEDs lit so far and the
+    /// blinking dot.
+    pub fn image(&self) -> [[u8; 5]; 5] {
+        let mut image = [[0; 5]; 5];
+        for (y, row) in image.iter_mut().enumerate() {
+            for (x, led) in row.iter_mut().enumerate() {
+                if self.lit & (1 << (y * 5 + x)) != 0 {
+                    *led = 9;
+                }
+            }
+        }
+        let (x, y) = self.cursor;
+        image[y][x] = if (self.samples / BLINK_SAMPLES) & 1 == 0 {
+            9
+        } else {
+            0
+        };
+        image
+    }
+
+    /// Returns the calibration, once every LED is lit.
+    pub fn calibration(&self) -> Option<Calibration> {
+        if !self.is_complete() {
+            return None;
+        }
+        let offset = Vector::new(
+            midpoint(self.min.x, self.max.x),
+            midpoint(self.min.y, self.max.y),
+            midpoint(self.min.z, self.max.z),
+        );
+        let spread = [
+            self.max.x - self.min.x,
+            self.max.y - self.min.y,
+            self.max.z - self.min.z,
+        ];
+        let average = spread.iter().map(|&spread| i64::from(spread)).sum::<i64>() / 3;
+        let [x, y, z] = spread.map(|spread| {
+            if spread == 0 {
+                UNIT_SCALE
+            } else {
+                (average * i64::from(UNIT_SCALE) / i64::from(spread)) as i32
+            }
+        });
+        Some(Calibration::new(offset, Vector::new(x, y, z)))
+    }
+}
+
+impl Default for Calibrator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Column, or row from the bottom, of the screen for a tilt along an axis.
+fn tilt_step(acceleration: i32) -> usize {
+    ((acceleration + TILT_STEP * 5 / 2) / TILT_STEP).clamp(0, 4) as usize
+}
+
+fn midpoint(a: i32, b: i32) -> i32 {
+    ((i64::from(a) + i64::from(b)) / 2) as i32
+}
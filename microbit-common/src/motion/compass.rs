@@ -0,0 +1,351 @@
+//! Compass combining the magnetometer and the accelerometer.
+
+use super::{
+    accelerometer::{Accelerometer, Error, Model, FXOS_M_CTRL_REG1, NXP_CTRL_REG1},
+    Calibration, Calibrator, Vector,
+};
+use embedded_hal::i2c::I2c;
+
+/// The magnetometers found on micro:bits.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Magnetometer {
+    /// NXP MAG3110, next to the MMA8653 accelerometer on early micro:bit V1
+    /// boards.
+    Mag3110,
+    /// The magnetometer of the NXP FXOS8700CQ.
+    Fxos8700,
+    /// The magnetometer of the ST LSM303AGR.
+    Lsm303agr,
+}
+
+impl Magnetometer {
+    /// The magnetometer fitted with an accelerometer model.
+    const fn with(accelerometer: Model) -> Self {
+        match accelerometer {
+            Model::Mma8653 => Magnetometer::Mag3110,
+            Model::Fxos8700 => Magnetometer::Fxos8700,
+            Model::Lsm303agr => Magnetometer::Lsm303agr,
+        }
+    }
+
+    /// 7-bit I2C address of the magnetometer.
+    pub const fn address(self) -> u8 {
+        match self {
+            Magnetometer::Mag3110 => 0x0E,
+            Magnetometer::Fxos8700 | Magnetometer::Lsm303agr => 0x1E,
+        }
+    }
+
+    /// Register holding the identity of the chip, and its expected value.
+    const fn who_am_i(self) -> (u8, u8) {
+        match self {
+            Magnetometer::Mag3110 => (0x07, 0xC4),
+            Magnetometer::Fxos8700 => (0x0D, 0xC7),
+            Magnetometer::Lsm303agr => (0x4F, 0x40),
+        }
+    }
+
+    /// Field measured by one unit, in nT.
+    const fn nt_per_unit(self) -> i32 {
+        match self {
+            Magnetometer::Mag3110 | Magnetometer::Fxos8700 => 100,
+            Magnetometer::Lsm303agr => 150,
+        }
+    }
+
+    /// Turn the sensor axes into the board ones, like the accelerometer of
+    /// the same chip or next to it. The field isn't negated like the
+    /// acceleration is.
+    const fn orient(self, [x, y, z]: [i32; 3]) -> Vector {
+        match self {
+            Magnetometer::Mag3110 | Magnetometer::Lsm303agr => Vector::new(-x, y, -z),
+            Magnetometer::Fxos8700 => Vector::new(y, x, -z),
+        }
+    }
+}
+
+// MAG3110 registers
+const MAG_OUT_X_MSB: u8 = 0x01;
+const MAG_CTRL_REG1: u8 = 0x10;
+const MAG_CTRL_REG2: u8 = 0x11;
+// 80Hz and active
+const MAG_CTRL_REG1_ACTIVE: u8 = 0x01;
+// reset the sensor before each measurement
+const MAG_CTRL_REG2_AUTO_MRST: u8 = 0x80;
+
+// FXOS8700 registers
+const FXOS_M_OUT_X_MSB: u8 = 0x33;
+// both sensors with the most oversampling, which halves the data rate
+const FXOS_M_CTRL_REG1_HYBRID: u8 = 0x1F;
+
+// LSM303AGR registers
+const LSM_CFG_REG_A_M: u8 = 0x60;
+const LSM_CFG_REG_C_M: u8 = 0x62;
+const LSM_OUTX_L_REG_M: u8 = 0x68;
+// temperature compensated, continuous at 50Hz
+const LSM_CFG_REG_A_M_50HZ: u8 = 0x88;
+// block data update
+const LSM_CFG_REG_C_M_BDU: u8 = 0x10;
+
+/// The compass of the micro:bit, whichever magnetometer it has.
+///
+/// The compass shares the I2C bus of the [`Accelerometer`] it is built
+/// from, which it also needs to tell which way is down: the heading stays
+/// right when the board isn't held flat.
+///
+/// The heading is only as good as the [`Calibration`] of the compass, see
+/// [`calibrate`](Compass::calibrate).
+///
+/// # Example
+///
+/// ```no_run
+/// # use microbit_common as microbit;
+/// # use embedded_hal::i2c::I2c;
+/// # fn example<I2C: I2c>(i2c: I2C) -> Result<(), microbit::motion::Error<I2C::Error>> {
+/// use microbit::motion::{Accelerometer, Compass};
+///
+/// let mut compass = Compass::new(Accelerometer::new(i2c)?)?;
+/// if let Some(heading) = compass.heading()? {
+///     // 0 when the logo points north, 90 to the east
+/// }
+/// // about 50000nT in most places
+/// let strength = compass.field_strength()?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct Compass<I2C> {
+    accelerometer: Accelerometer<I2C>,
+    magnetometer: Magnetometer,
+    calibration: Option<Calibration>,
+}
+
+impl<I2C: I2c> Compass<I2C> {
+    /// Find the magnetometer next to `accelerometer` and start it.
+    pub fn new(mut accelerometer: Accelerometer<I2C>) -> Result<Self, Error<I2C::Error>> {
+        let magnetometer = Magnetometer::with(accelerometer.model());
+        let address = magnetometer.address();
+        let (register, id) = magnetometer.who_am_i();
+        let mut value = [0];
+        accelerometer
+            .i2c()
+            .write_read(address, &[register], &mut value)?;
+        if value[0] != id {
+            return Err(Error::NotFound);
+        }
+
+        let i2c = accelerometer.i2c();
+        match magnetometer {
+            Magnetometer::Mag3110 => {
+                i2c.write(address, &[MAG_CTRL_REG2, MAG_CTRL_REG2_AUTO_MRST])?;
+                i2c.write(address, &[MAG_CTRL_REG1, MAG_CTRL_REG1_ACTIVE])?;
+            }
+            Magnetometer::Fxos8700 => {
+                // the magnetometer can only be enabled in standby
+                i2c.write(address, &[NXP_CTRL_REG1, 0])?;
+                i2c.write(address, &[FXOS_M_CTRL_REG1, FXOS_M_CTRL_REG1_HYBRID])?;
+                accelerometer.configure()?;
+            }
+            Magnetometer::Lsm303agr => {
+                i2c.write(address, &[LSM_CFG_REG_A_M, LSM_CFG_REG_A_M_50HZ])?;
+                i2c.write(address, &[LSM_CFG_REG_C_M, LSM_CFG_REG_C_M_BDU])?;
+            }
+        }
+
+        Ok(Self {
+            accelerometer,
+            magnetometer,
+            calibration: None,
+        })
+    }
+
+    /// Returns the model of the magnetometer.
+    pub fn magnetometer(&self) -> Magnetometer {
+        self.magnetometer
+    }
+
+    /// Returns the accelerometer the compass uses.
+    pub fn accelerometer(&mut self) -> &mut Accelerometer<I2C> {
+        &mut self.accelerometer
+    }
+
+    /// Returns the calibration of the compass, if any.
+    pub fn calibration(&self) -> Option<Calibration> {
+        self.calibration
+    }
+
+    /// Set the calibration applied to the magnetic field, like one saved by
+    /// an earlier [`calibrate`](Self::calibrate).
+    pub fn set_calibration(&mut self, calibration: Option<Calibration>) {
+        self.calibration = calibration;
+    }
+
+    /// Play the "fill the screen" game to calibrate the compass, and use the
+    /// calibration from then on.
+    ///
+    /// `show` is called with the screen to display after each sample, and
+    /// should display it for about 20ms, the time between two samples. See
+    /// [`Calibrator`] for the rules of the game.
+    ///
+    /// ```no_run
+    /// # use microbit_common as microbit;
+    /// # use embedded_hal::i2c::I2c;
+    /// # fn example<I2C: I2c>(
+    /// #     compass: &mut microbit::motion::Compass<I2C>,
+    /// #     display: &mut microbit::display::blocking::Display,
+    /// #     timer: &mut impl embedded_hal::delay::DelayNs,
+    /// # ) -> Result<(), microbit::motion::Error<I2C::Error>> {
+    /// let calibration = compass.calibrate(|image| display.show(timer, image, 20))?;
+    /// // save it to flash, to skip the game next time
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn calibrate(
+        &mut self,
+        mut show: impl FnMut([[u8; 5]; 5]),
+    ) -> Result<Calibration, Error<I2C::Error>> {
+        let mut calibrator = Calibrator::new();
+        loop {
+            let acceleration = self.accelerometer.acceleration()?;
+            let field = self.raw_field()?;
+            calibrator.update(acceleration, field);
+            show(calibrator.image());
+            if let Some(calibration) = calibrator.calibration() {
+                self.calibration = Some(calibration);
+                return Ok(calibration);
+            }
+        }
+    }
+
+    /// Returns the latest magnetic field, in nT, along the axes described in
+    /// [`Vector`], corrected by the calibration.
+    pub fn field(&mut self) -> Result<Vector, Error<I2C::Error>> {
+        let field = self.raw_field()?;
+        Ok(match self.calibration {
+            Some(calibration) => calibration.apply(field),
+            None => field,
+        })
+    }
+
+    /// Returns the latest magnetic field, in nT, without calibration.
+    pub fn raw_field(&mut self) -> Result<Vector, Error<I2C::Error>> {
+        let address = self.magnetometer.address();
+        let i2c = self.accelerometer.i2c();
+        let mut data = [0; 6];
+        let raw = match self.magnetometer {
+            Magnetometer::Mag3110 | Magnetometer::Fxos8700 => {
+                let register = if self.magnetometer == Magnetometer::Mag3110 {
+                    MAG_OUT_X_MSB
+                } else {
+                    FXOS_M_OUT_X_MSB
+                };
+                i2c.write_read(address, &[register], &mut data)?;
+                [0, 2, 4].map(|i| i16::from_be_bytes([data[i], data[i + 1]]))
+            }
+            Magnetometer::Lsm303agr => {
+                i2c.write_read(address, &[LSM_OUTX_L_REG_M], &mut data)?;
+                [0, 2, 4].map(|i| i16::from_le_bytes([data[i], data[i + 1]]))
+            }
+        };
+        let nt_per_unit = self.magnetometer.nt_per_unit();
+        Ok(self
+            .magnetometer
+            .orient(raw.map(|value| i32::from(value) * nt_per_unit)))
+    }
+
+    /// Returns the strength of the latest magnetic field, in nT.
+    pub fn field_strength(&mut self) -> Result<u32, Error<I2C::Error>> {
+        Ok(self.field()?.magnitude())
+    }
+
+    /// Returns the heading of the logo, in degrees clockwise from magnetic
+    /// north.
+    ///
+    /// Returns `None` when there is no way to tell, like when the board is
+    /// falling. See [`heading`] for the details.
+    pub fn heading(&mut self) -> Result<Option<u16>, Error<I2C::Error>> {
+        let field = self.field()?;
+        let acceleration = self.accelerometer.acceleration()?;
+        Ok(heading(field, acceleration))
+    }
+
+    /// Give back the accelerometer, leaving the magnetometer running.
+    pub fn free(self) -> Accelerometer<I2C> {
+        self.accelerometer
+    }
+}
+
+/// Returns the direction the logo points to, in degrees clockwise from
+/// magnetic north, from the magnetic `field` and the `acceleration` of the
+/// board at rest.
+///
+/// The acceleration tells which way is down, so the horizontal part of the
+/// field, which points north, can be found whichever way the board is
+/// tilted. The heading is that of the logo as seen from above, so it gets
+/// unreliable as the logo points straight up or down.
+///
+/// Returns `None` when the field or the acceleration is zero, or when they
+/// point the same way.
+///
+/// ```
+/// # use microbit_common as microbit;
+/// use microbit::motion::{heading, Vector};
+///
+/// // lying screen up, with the field pointing north and into the ground
+/// let down = Vector::new(0, 0, -1000);
+/// assert_eq!(heading(Vector::new(0, 20_000, -45_000), down), Some(0));
+/// // the logo points east, so north is on the left
+/// assert_eq!(heading(Vector::new(-20_000, 0, -45_000), down), Some(90));
+///
+/// // the logo still points east, tilted up by about 30 degrees
+/// let tilted = Vector::new(0, -500, -866);
+/// let field = Vector::new(-20_000, -22_500, -38_971);
+/// assert_eq!(heading(field, tilted), Some(90));
+/// ```
+pub fn heading(field: Vector, acceleration: Vector) -> Option<u16> {
+    let down = [acceleration.x, acceleration.y, acceleration.z].map(i64::from);
+    let field = [field.x, field.y, field.z].map(i64::from);
+    // east is across down and the field, north across east and down, which
+    // makes it longer by the length of down
+    let east = cross(down, field);
+    let north = cross(east, down);
+    // the logo is along the y axis
+    bearing(east[1] * i64::from(acceleration.magnitude()), north[1])
+}
+
+fn cross(a: [i64; 3], b: [i64; 3]) -> [i64; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+/// Angle of the vector `(x, y)` from the y axis towards the x axis, in
+/// degrees from 0 to 359.
+fn bearing(x: i64, y: i64) -> Option<u16> {
+    let (abs_x, abs_y) = (x.unsigned_abs(), y.unsigned_abs());
+    let max = abs_x.max(abs_y);
+    if max == 0 {
+        return None;
+    }
+    // tan of the angle to the nearest axis, with 16 fractional bits, with
+    // the values scaled down to keep the shift in range
+    let scale = (64 - max.leading_zeros()).saturating_sub(40);
+    let t = (((abs_x.min(abs_y) >> scale) << 16) / (max >> scale).max(1)) as i64;
+    // atan(t) in degrees, within 0.1 degree, with 16 fractional bits
+    let atan = 45 * t + ((((t * ((1 << 16) - t)) >> 16) * (918_241 + ((249_037 * t) >> 16))) >> 16);
+
+    // angle in the first quadrant
+    let mut angle = if abs_x <= abs_y {
+        atan
+    } else {
+        (90 << 16) - atan
+    };
+    if y < 0 {
+        angle = (180 << 16) - angle;
+    }
+    if x < 0 {
+        angle = (360 << 16) - angle;
+    }
+    Some((((angle + (1 << 15)) >> 16) % 360) as u16)
+}
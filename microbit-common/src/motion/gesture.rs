@@ -0,0 +1,335 @@
+//! Gesture recognition, independent of the hardware.
+
+use super::Vector;
+
+/// Margin below 1g within which an axis counts as pointing down, in milli-g:
+/// an axis is tilted down past `1000 - TILT_TOLERANCE`.
+const TILT_TOLERANCE: i32 = 200;
+
+/// Acceleration below which the board is falling, in milli-g.
+const FREEFALL_TOLERANCE: i32 = 400;
+
+/// Acceleration an axis must swing past, both ways, to count as a shake, in
+/// milli-g.
+const SHAKE_TOLERANCE: i32 = 400;
+
+/// Number of swings in a row making a shake.
+const SHAKE_COUNT_THRESHOLD: u8 = 4;
+
+/// Number of samples after which a swing is forgotten.
+const SHAKE_DAMPING: u8 = 10;
+
+/// Number of samples after a shake before the next one can be reported.
+const SHAKE_RTX: u8 = 30;
+
+/// Number of samples a posture must last to be reported.
+const GESTURE_DAMPING: u8 = 5;
+
+/// Acceleration below which impulses can be reported again, in milli-g.
+const IMPULSE_RESET: i32 = 2048;
+
+/// Impulse gestures and their thresholds, in milli-g.
+const IMPULSES: [(Gesture, i32); 3] = [
+    (Gesture::ThreeG, 3072),
+    (Gesture::SixG, 6144),
+    (Gesture::EightG, 8192),
+];
+
+/// Gestures of the board, like the ones of MakeCode's `input.onGesture`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Gesture {
+    /// The board is being shaken.
+    Shake,
+    /// The logo is up, the board standing on its edge connector.
+    LogoUp,
+    /// The logo is down.
+    LogoDown,
+    /// The display faces up.
+    ScreenUp,
+    /// The display faces down.
+    ScreenDown,
+    /// The board is tilted onto its left edge.
+    TiltLeft,
+    /// The board is tilted onto its right edge.
+    TiltRight,
+    /// The board is falling.
+    FreeFall,
+    /// An impulse stronger than 3g.
+    ThreeG,
+    /// An impulse stronger than 6g.
+    SixG,
+    /// An impulse stronger than 8g, which needs the accelerometer in its
+    /// ±8g range.
+    EightG,
+}
+
+impl Gesture {
+    /// Every gesture, in the order [`Gestures`] yields them.
+    const ALL: [Gesture; 11] = [
+        Gesture::Shake,
+        Gesture::LogoUp,
+        Gesture::LogoDown,
+        Gesture::ScreenUp,
+        Gesture::ScreenDown,
+        Gesture::TiltLeft,
+        Gesture::TiltRight,
+        Gesture::FreeFall,
+        Gesture::ThreeG,
+        Gesture::SixG,
+        Gesture::EightG,
+    ];
+
+    const fn bit(self) -> u16 {
+        1 << self as u16
+    }
+}
+
+/// The gestures reported by one [`GestureDetector::update`].
+///
+/// Iterating yields them in the order of [`Gesture`]'s variants.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Gestures(u16);
+
+impl Gestures {
+    /// Returns `true` if no gesture was reported.
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Returns `true` if `gesture` was reported.
+    pub fn contains(&self, gesture: Gesture) -> bool {
+        self.0 & gesture.bit() != 0
+    }
+
+    fn insert(&mut self, gesture: Gesture) {
+        self.0 |= gesture.bit();
+    }
+}
+
+impl Iterator for Gestures {
+    type Item = Gesture;
+
+    fn next(&mut self) -> Option<Gesture> {
+        let gesture = Gesture::ALL
+            .get(self.0.trailing_zeros() as usize)
+            .copied()?;
+        self.0 &= !gesture.bit();
+        Some(gesture)
+    }
+}
+
+/// Shake detection state, counting swings of the acceleration.
+#[derive(Copy, Clone, Debug, Default)]
+struct Shake {
+    // sign each axis must swing to next
+    x: bool,
+    y: bool,
+    z: bool,
+    count: u8,
+    timer: u8,
+    shaken: bool,
+}
+
+/// Recognises [`Gesture`]s from accelerometer samples, like the micro:bit
+/// firmware does.
+///
+/// The thresholds and debouncing are the ones of CODAL, the runtime behind
+/// MakeCode, and count in samples: feed the detector the 50Hz samples of the
+/// [`Accelerometer`](super::Accelerometer), in milli-g.
+///
+/// Postures (the logo, screen and tilt gestures, and free fall) are reported
+/// once they have lasted 5 samples, and again only after another posture.
+/// A shake is 4 strong swings in a row and can be reported again 30 samples
+/// later. Impulses are reported as soon as they happen, and again once the
+/// acceleration has dropped below 2g.
+///
+/// The detector doesn't touch any hardware, so it can be tested with recorded
+/// or made up samples:
+///
+/// ```
+/// # use microbit_common as microbit;
+/// use microbit::motion::{Gesture, GestureDetector, Vector};
+///
+/// let mut detector = GestureDetector::new();
+/// let mut gestures = vec![];
+/// let mut feed = |detector: &mut GestureDetector, samples: &[(i32, i32, i32)]| {
+///     for &(x, y, z) in samples {
+///         gestures.extend(detector.update(Vector::new(x, y, z)));
+///     }
+/// };
+///
+/// // lying on a table, then tilted onto its right edge
+/// feed(&mut detector, &[(0, 0, -1000); 10]);
+/// // nudged by about 15°, still screen up
+/// feed(&mut detector, &[(250, 0, -970); 10]);
+/// feed(&mut detector, &[(900, 0, -400); 10]);
+/// // shaken left and right, then put back down
+/// feed(&mut detector, &[(-1500, 0, -1000), (1500, 0, -1000)].repeat(3));
+/// feed(&mut detector, &[(0, 0, -1000); 40]);
+/// // dropped, hitting the floor hard
+/// feed(&mut detector, &[(0, 0, 0); 10]);
+/// feed(&mut detector, &[(0, 0, -7000), (0, 0, -1000)]);
+///
+/// use Gesture::*;
+/// assert_eq!(
+///     gestures,
+///     [ScreenUp, TiltRight, Shake, ScreenUp, FreeFall, ThreeG, SixG]
+/// );
+/// assert_eq!(detector.current(), Some(FreeFall));
+/// ```
+#[derive(Copy, Clone, Debug, Default)]
+pub struct GestureDetector {
+    shake: Shake,
+    // impulses reported since the acceleration last went below 2g
+    impulses: Gestures,
+    // posture being debounced, and for how many samples
+    candidate: Option<Gesture>,
+    sigma: u8,
+    // last posture reported
+    current: Option<Gesture>,
+}
+
+impl GestureDetector {
+    /// Create a detector with no gesture yet.
+    pub const fn new() -> Self {
+        Self {
+            shake: Shake {
+                x: false,
+                y: false,
+                z: false,
+                count: 0,
+                timer: 0,
+                shaken: false,
+            },
+            impulses: Gestures(0),
+            candidate: None,
+            sigma: 0,
+            current: None,
+        }
+    }
+
+    /// Returns the last posture or shake reported, if any.
+    pub fn current(&self) -> Option<Gesture> {
+        self.current
+    }
+
+    /// Feed the next acceleration sample, in milli-g.
+    ///
+    /// Returns the gestures recognised with this sample, usually none.
+    pub fn update(&mut self, acceleration: Vector) -> Gestures {
+        let mut gestures = Gestures::default();
+        let force = squared_magnitude(acceleration);
+
+        // impulses aren't filtered, they are short by nature
+        for (gesture, threshold) in IMPULSES {
+            if force > squared(threshold) && !self.impulses.contains(gesture) {
+                self.impulses.insert(gesture);
+                gestures.insert(gesture);
+            }
+        }
+        if force < squared(IMPULSE_RESET) {
+            self.impulses = Gestures::default();
+        }
+
+        let (posture, new_shake) = self.posture(acceleration, force);
+        if posture == Some(Gesture::Shake) {
+            // the shake takes over any posture while it lasts
+            self.current = posture;
+            if new_shake {
+                gestures.insert(Gesture::Shake);
+            }
+            return gestures;
+        }
+
+        if posture == self.candidate {
+            if self.sigma < GESTURE_DAMPING {
+                self.sigma += 1;
+            }
+        } else {
+            self.candidate = posture;
+            self.sigma = 0;
+        }
+        if self.candidate != self.current && self.sigma >= GESTURE_DAMPING {
+            self.current = self.candidate;
+            if let Some(gesture) = self.current {
+                gestures.insert(gesture);
+            }
+        }
+        gestures
+    }
+
+    /// Returns the posture of the board in this sample, or
+    /// [`Gesture::Shake`] while it is being shaken, and whether a new shake
+    /// was detected.
+    fn posture(&mut self, acceleration: Vector, force: u64) -> (Option<Gesture>, bool) {
+        let shake = &mut self.shake;
+
+        // a swing is a strong acceleration the other way than the last one
+        let mut swung = false;
+        for (value, positive) in [
+            (acceleration.x, &mut shake.x),
+            (acceleration.y, &mut shake.y),
+            (acceleration.z, &mut shake.z),
+        ] {
+            if (value < -SHAKE_TOLERANCE && *positive) || (value > SHAKE_TOLERANCE && !*positive) {
+                swung = true;
+                *positive = !*positive;
+            }
+        }
+
+        if swung && shake.count < SHAKE_COUNT_THRESHOLD {
+            shake.count += 1;
+            if shake.count == 1 {
+                shake.timer = 0;
+            }
+            if shake.count == SHAKE_COUNT_THRESHOLD {
+                shake.shaken = true;
+                shake.timer = 0;
+                return (Some(Gesture::Shake), true);
+            }
+        }
+
+        if shake.count > 0 {
+            shake.timer += 1;
+            if shake.shaken && shake.timer >= SHAKE_RTX {
+                // allow the next shake
+                shake.shaken = false;
+                shake.timer = 0;
+                shake.count = 0;
+            } else if !shake.shaken && shake.timer >= SHAKE_DAMPING {
+                // forget swings of slow movements
+                shake.timer = 0;
+                shake.count -= 1;
+            }
+        }
+
+        let posture = if shake.shaken {
+            Gesture::Shake
+        } else if force < squared(FREEFALL_TOLERANCE) {
+            Gesture::FreeFall
+        } else if acceleration.x < -1000 + TILT_TOLERANCE {
+            Gesture::TiltLeft
+        } else if acceleration.x > 1000 - TILT_TOLERANCE {
+            Gesture::TiltRight
+        } else if acceleration.y < -1000 + TILT_TOLERANCE {
+            Gesture::LogoUp
+        } else if acceleration.y > 1000 - TILT_TOLERANCE {
+            Gesture::LogoDown
+        } else if acceleration.z < -1000 + TILT_TOLERANCE {
+            Gesture::ScreenUp
+        } else if acceleration.z > 1000 - TILT_TOLERANCE {
+            Gesture::ScreenDown
+        } else {
+            return (None, false);
+        };
+        (Some(posture), false)
+    }
+}
+
+fn squared(value: i32) -> u64 {
+    i64::from(value).pow(2) as u64
+}
+
+fn squared_magnitude(vector: Vector) -> u64 {
+    squared(vector.x) + squared(vector.y) + squared(vector.z)
+}
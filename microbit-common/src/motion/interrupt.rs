@@ -0,0 +1,144 @@
+//! Accelerometer samples delivered by its interrupt line.
+
+use super::{Accelerometer, Error, Interrupt, Vector};
+use crate::{
+    gpio::INT_ACC,
+    hal::{
+        gpio::{Input, Pin, PullUp},
+        gpiote::GpioteChannel,
+    },
+};
+use embedded_hal::i2c::I2c;
+
+/// An [`Accelerometer`] waking the CPU up through a GPIOTE channel.
+///
+/// The accelerometer signals an [`Interrupt`] on the [`INT_ACC`] pin, which
+/// sets a GPIOTE event and raises the `GPIOTE` interrupt: there is no need to
+/// poll the sensor, the samples come in from the interrupt handler, or after
+/// `wfi`.
+///
+/// # Example
+///
+/// ```no_run
+/// # use microbit_common as microbit;
+/// use microbit::{
+///     hal::gpiote::Gpiote,
+///     motion::{Accelerometer, Interrupt, InterruptAccelerometer},
+///     pac, Board,
+/// };
+/// # #[cfg(feature = "v1")]
+/// # use microbit::{hal::twi::Twi as I2c, pac::twi0::frequency::FREQUENCY_A};
+/// # #[cfg(feature = "v2")]
+/// # use microbit::{hal::twim::Twim as I2c, pac::twim0::frequency::FREQUENCY_A};
+///
+/// let board = Board::take().unwrap();
+/// # #[cfg(feature = "v1")]
+/// # let i2c = I2c::new(board.TWI0, board.i2c.into(), FREQUENCY_A::K100);
+/// # #[cfg(feature = "v2")]
+/// let i2c = I2c::new(board.TWIM0, board.i2c_internal.into(), FREQUENCY_A::K100);
+/// let gpiote = Gpiote::new(board.GPIOTE);
+///
+/// let accelerometer = Accelerometer::new(i2c).unwrap();
+/// let mut accelerometer = InterruptAccelerometer::new(
+///     accelerometer,
+///     board.int_acc,
+///     &gpiote.channel0(),
+///     Interrupt::DataReady,
+/// )
+/// .unwrap();
+/// unsafe { pac::NVIC::unmask(pac::Interrupt::GPIOTE) };
+///
+/// loop {
+///     // in the GPIOTE interrupt handler
+///     if let Ok(Some(acceleration)) = accelerometer.handle_interrupt(&gpiote.channel0()) {
+///         // a new sample, 50 times a second
+///     }
+/// }
+/// ```
+pub struct InterruptAccelerometer<I2C> {
+    accelerometer: Accelerometer<I2C>,
+    pin: Pin<Input<PullUp>>,
+    interrupt: Interrupt,
+}
+
+impl<I2C: I2c> InterruptAccelerometer<I2C> {
+    /// Enable `interrupt` on the accelerometer, and a falling edge of `pin`
+    /// as the event and interrupt of the GPIOTE `channel`.
+    pub fn new(
+        mut accelerometer: Accelerometer<I2C>,
+        pin: INT_ACC,
+        channel: &GpioteChannel<'_>,
+        interrupt: Interrupt,
+    ) -> Result<Self, Error<I2C::Error>> {
+        let pin = pin.degrade();
+        accelerometer.enable_interrupt(interrupt)?;
+        channel.input_pin(&pin).hi_to_lo().enable_interrupt();
+        channel.reset_events();
+
+        let mut accelerometer = Self {
+            accelerometer,
+            pin,
+            interrupt,
+        };
+        // an event already pending holds the line low, and no falling edge
+        // would ever come
+        accelerometer.acknowledge()?;
+        Ok(accelerometer)
+    }
+
+    /// Returns the accelerometer, to change its range.
+    ///
+    /// Enable the interrupt again after changing the range.
+    pub fn accelerometer(&mut self) -> &mut Accelerometer<I2C> {
+        &mut self.accelerometer
+    }
+
+    /// Returns the interrupt enabled on the accelerometer.
+    pub fn interrupt(&self) -> Interrupt {
+        self.interrupt
+    }
+
+    /// Enable `interrupt` on the accelerometer instead of the current one.
+    pub fn set_interrupt(&mut self, interrupt: Interrupt) -> Result<(), Error<I2C::Error>> {
+        self.accelerometer.enable_interrupt(interrupt)?;
+        self.interrupt = interrupt;
+        self.acknowledge()?;
+        Ok(())
+    }
+
+    /// Handle the GPIOTE event of `channel`, the one given to
+    /// [`new`](Self::new), from the `GPIOTE` interrupt handler.
+    ///
+    /// Returns the acceleration, in milli-g, if the accelerometer signalled
+    /// its interrupt, and clears the event so the next one can come.
+    pub fn handle_interrupt(
+        &mut self,
+        channel: &GpioteChannel<'_>,
+    ) -> Result<Option<Vector>, Error<I2C::Error>> {
+        if !channel.is_event_triggered() {
+            return Ok(None);
+        }
+        channel.reset_events();
+        self.acknowledge().map(Some)
+    }
+
+    /// Disable the interrupt of the GPIOTE `channel` and give back the
+    /// accelerometer and the pin.
+    ///
+    /// The accelerometer keeps signalling its interrupt, until
+    /// [`Accelerometer::disable_interrupt`] is called.
+    pub fn free(self, channel: &GpioteChannel<'_>) -> (Accelerometer<I2C>, Pin<Input<PullUp>>) {
+        channel.input_pin(&self.pin).disable_interrupt();
+        channel.reset_events();
+        (self.accelerometer, self.pin)
+    }
+
+    /// Read the acceleration and release the interrupt line.
+    fn acknowledge(&mut self) -> Result<Vector, Error<I2C::Error>> {
+        let acceleration = self.accelerometer.acceleration()?;
+        if let Interrupt::Motion { .. } = self.interrupt {
+            self.accelerometer.clear_interrupt()?;
+        }
+        Ok(acceleration)
+    }
+}
@@ -0,0 +1,320 @@
+//! Motion data logging to flash.
+
+use super::Vector;
+use core::fmt;
+use embedded_storage::nor_flash::NorFlash;
+
+/// Length of a sample in flash, in bytes.
+pub const SAMPLE_LEN: u32 = 36;
+
+/// Sequence number of an erased sample slot.
+const ERASED: u32 = u32::MAX;
+
+/// Set in the flags of samples with a field.
+const HAS_FIELD: u32 = 1 << 0;
+
+/// A motion sample, as logged.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Sample {
+    /// Time of the sample, in milliseconds from any start.
+    pub time_ms: u32,
+    /// Acceleration, in milli-g.
+    pub acceleration: Vector,
+    /// Magnetic field, in nT, if logged.
+    pub field: Option<Vector>,
+}
+
+impl Sample {
+    /// Returns the sample as a slot of flash, its sequence number apart: the
+    /// flags, the time, the acceleration and the field, a word each.
+    fn to_bytes(self) -> [u8; SAMPLE_LEN as usize] {
+        let (flags, field) = match self.field {
+            Some(field) => (HAS_FIELD, field),
+            None => (0, Vector::new(0, 0, 0)),
+        };
+        let words = [
+            flags,
+            self.time_ms,
+            self.acceleration.x as u32,
+            self.acceleration.y as u32,
+            self.acceleration.z as u32,
+            field.x as u32,
+            field.y as u32,
+            field.z as u32,
+        ];
+        let mut bytes = [0xFF; SAMPLE_LEN as usize];
+        for (chunk, word) in bytes[4..].chunks_exact_mut(4).zip(words) {
+            chunk.copy_from_slice(&word.to_le_bytes());
+        }
+        bytes
+    }
+
+    fn from_bytes(bytes: &[u8; SAMPLE_LEN as usize]) -> Self {
+        let mut words = [0; SAMPLE_LEN as usize / 4];
+        for (word, chunk) in words.iter_mut().zip(bytes.chunks_exact(4)) {
+            *word = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        }
+        let vector =
+            |at: usize| Vector::new(words[at] as i32, words[at + 1] as i32, words[at + 2] as i32);
+        Self {
+            time_ms: words[2],
+            acceleration: vector(3),
+            field: if words[1] & HAS_FIELD != 0 {
+                Some(vector(6))
+            } else {
+                None
+            },
+        }
+    }
+}
+
+/// Errors writing a [`MotionLog`] as CSV.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CsvError<E> {
+    /// Reading the flash failed.
+    Flash(E),
+    /// Writing the text failed.
+    Write(fmt::Error),
+}
+
+impl<E: fmt::Debug> fmt::Display for CsvError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CsvError::Flash(error) => write!(f, "flash error: {:?}", error),
+            CsvError::Write(_) => f.write_str("could not write the CSV"),
+        }
+    }
+}
+
+/// A log of motion samples in a ring buffer in flash, like the NVMC.
+///
+/// The log takes whole erase pages of the flash, and keeps as many of the
+/// latest samples as fit in them: once full, the page holding the oldest
+/// samples is erased to make room. It picks up where it left off after a
+/// reset.
+///
+/// Each sample takes [`SAMPLE_LEN`] bytes: 7 pages of 4kB at the end of
+/// the flash of a micro:bit V2 hold 791 samples, 13 minutes at a sample a
+/// second. Only the hal of the V2 drives the NVMC as a [`NorFlash`], so the
+/// log is kept in the flash of the V2 only.
+///
+/// # Example
+///
+/// ```no_run
+/// # use microbit_common as microbit;
+/// # #[cfg(feature = "v2")]
+/// # {
+/// use microbit::{
+///     hal::nvmc::Nvmc,
+///     motion::{MotionLog, Sample, Vector},
+///     Board,
+/// };
+///
+/// // the end of the flash of the V2, not used by the program
+/// const START: usize = 0x78000;
+/// const LEN: usize = 7 * 4096;
+///
+/// let board = Board::take().unwrap();
+/// // Safety: nothing else uses those pages
+/// let pages = unsafe { core::slice::from_raw_parts_mut(START as *mut u8, LEN) };
+/// let mut log = MotionLog::new(Nvmc::new(board.NVMC, pages), 0, LEN as u32).unwrap();
+///
+/// log.push(&Sample {
+///     time_ms: 100,
+///     acceleration: Vector::new(0, 0, -1000),
+///     field: None,
+/// })
+/// .unwrap();
+///
+/// // then print it to a serial port, or anything implementing `fmt::Write`
+/// let mut csv = String::new();
+/// log.write_csv(&mut csv).unwrap();
+/// # struct String;
+/// # impl String { fn new() -> Self { String } }
+/// # impl core::fmt::Write for String {
+/// #     fn write_str(&mut self, _: &str) -> core::fmt::Result { Ok(()) }
+/// # }
+/// # }
+/// ```
+pub struct MotionLog<F> {
+    flash: F,
+    offset: u32,
+    slots: u32,
+    // slot and sequence number of the next sample
+    next: u32,
+    sequence: u32,
+    empty: bool,
+}
+
+impl<F: NorFlash> MotionLog<F> {
+    /// Open the log in the `len` bytes at `offset` in `flash`, finding the
+    /// samples already there.
+    ///
+    /// `offset` and `len` must be aligned to erase pages, and the pages
+    /// must not hold anything else: start with [`clear`](Self::clear) if
+    /// they do.
+    pub fn new(mut flash: F, offset: u32, len: u32) -> Result<Self, F::Error> {
+        let slots = len / F::ERASE_SIZE as u32 * Self::slots_per_page();
+        let mut latest = None;
+        for slot in 0..slots {
+            let mut sequence = [0; 4];
+            flash.read(Self::address(offset, slot), &mut sequence)?;
+            let sequence = u32::from_le_bytes(sequence);
+            let newer = match latest {
+                None => true,
+                Some((_, latest)) => sequence.wrapping_sub(latest) < u32::MAX / 2,
+            };
+            if sequence != ERASED && newer {
+                latest = Some((slot, sequence));
+            }
+        }
+        let mut log = Self {
+            flash,
+            offset,
+            slots,
+            next: 0,
+            sequence: 0,
+            empty: latest.is_none(),
+        };
+        if let Some((slot, sequence)) = latest {
+            log.next = (slot + 1) % slots;
+            log.sequence = sequence.wrapping_add(1) % ERASED;
+        }
+        Ok(log)
+    }
+
+    /// Returns the most samples the log holds.
+    ///
+    /// As the oldest page is erased to make room, the log holds at least a
+    /// page less than that once full.
+    pub fn capacity(&self) -> u32 {
+        self.slots
+    }
+
+    /// Returns `true` if the log holds no sample.
+    pub fn is_empty(&self) -> bool {
+        self.empty
+    }
+
+    /// Add `sample` to the log, after the latest one.
+    pub fn push(&mut self, sample: &Sample) -> Result<(), F::Error> {
+        if self.slots == 0 {
+            return Ok(());
+        }
+        let address = loop {
+            let address = Self::address(self.offset, self.next);
+            let index = self.next % Self::slots_per_page();
+            if index == 0 {
+                self.flash.erase(address, address + F::ERASE_SIZE as u32)?;
+                break address;
+            }
+            // a sample cut short by a reset left the slot unusable until the
+            // page is erased
+            let mut bytes = [0; SAMPLE_LEN as usize];
+            self.flash.read(address, &mut bytes)?;
+            if bytes.iter().all(|&byte| byte == 0xFF) {
+                break address;
+            }
+            self.next = (self.next + 1) % self.slots;
+        };
+        // the sequence number goes last, so a sample cut short is left out
+        self.flash.write(address + 4, &sample.to_bytes()[4..])?;
+        self.flash.write(address, &self.sequence.to_le_bytes())?;
+        self.next = (self.next + 1) % self.slots;
+        self.sequence = self.sequence.wrapping_add(1) % ERASED;
+        self.empty = false;
+        Ok(())
+    }
+
+    /// Returns the samples, from the oldest to the latest.
+    pub fn samples(&mut self) -> Samples<'_, F> {
+        let remaining = if self.empty { 0 } else { self.slots };
+        Samples {
+            next: self.next,
+            log: self,
+            remaining,
+        }
+    }
+
+    /// Write the samples as CSV, from the oldest to the latest.
+    ///
+    /// There is a line per sample after a header, with the time in
+    /// milliseconds, the acceleration in milli-g and the field in nT, left
+    /// empty for samples without one:
+    ///
+    /// ```text
+    /// time_ms,x_mg,y_mg,z_mg,x_nt,y_nt,z_nt
+    /// 100,12,-20,-1004,,,
+    /// ```
+    pub fn write_csv<W: fmt::Write>(&mut self, out: &mut W) -> Result<(), CsvError<F::Error>> {
+        out.write_str("time_ms,x_mg,y_mg,z_mg,x_nt,y_nt,z_nt\r\n")
+            .map_err(CsvError::Write)?;
+        for sample in self.samples() {
+            let sample = sample.map_err(CsvError::Flash)?;
+            let Vector { x, y, z } = sample.acceleration;
+            write!(out, "{},{},{},{},", sample.time_ms, x, y, z).map_err(CsvError::Write)?;
+            match sample.field {
+                Some(Vector { x, y, z }) => write!(out, "{},{},{}\r\n", x, y, z),
+                None => out.write_str(",,\r\n"),
+            }
+            .map_err(CsvError::Write)?;
+        }
+        Ok(())
+    }
+
+    /// Erase all the samples.
+    pub fn clear(&mut self) -> Result<(), F::Error> {
+        let pages = self.slots / Self::slots_per_page();
+        self.flash
+            .erase(self.offset, self.offset + pages * F::ERASE_SIZE as u32)?;
+        self.next = 0;
+        self.sequence = 0;
+        self.empty = true;
+        Ok(())
+    }
+
+    /// Give back the flash.
+    pub fn free(self) -> F {
+        self.flash
+    }
+
+    fn slots_per_page() -> u32 {
+        F::ERASE_SIZE as u32 / SAMPLE_LEN
+    }
+
+    /// Returns the address of a sample slot.
+    fn address(offset: u32, slot: u32) -> u32 {
+        let page = slot / Self::slots_per_page();
+        let index = slot % Self::slots_per_page();
+        offset + page * F::ERASE_SIZE as u32 + index * SAMPLE_LEN
+    }
+}
+
+/// Iterator over the samples of a [`MotionLog`], see
+/// [`MotionLog::samples`].
+pub struct Samples<'a, F> {
+    log: &'a mut MotionLog<F>,
+    next: u32,
+    remaining: u32,
+}
+
+impl<F: NorFlash> Iterator for Samples<'_, F> {
+    type Item = Result<Sample, F::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.remaining > 0 {
+            let address = MotionLog::<F>::address(self.log.offset, self.next);
+            self.next = (self.next + 1) % self.log.slots;
+            self.remaining -= 1;
+
+            let mut bytes = [0; SAMPLE_LEN as usize];
+            if let Err(error) = self.log.flash.read(address, &mut bytes) {
+                return Some(Err(error));
+            }
+            if bytes[..4] != ERASED.to_le_bytes() {
+                return Some(Ok(Sample::from_bytes(&bytes)));
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,98 @@
+//! The on-board motion sensors.
+//!
+//! Depending on its revision, a micro:bit V1 has either an MMA8653 or an
+//! FXOS8700 accelerometer, or an LSM303AGR; the V2 has an LSM303AGR. They
+//! all sit on the internal I2C bus, and [`Accelerometer`] finds out which one
+//! is fitted so the same code runs on every board. [`Compass`] adds the
+//! magnetometer that comes with each of them, and [`InterruptAccelerometer`]
+//! delivers the samples through the interrupt line of the sensor.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # use microbit_common as microbit;
+//! use microbit::{motion::Accelerometer, Board};
+//! # #[cfg(feature = "v1")]
+//! # use microbit::{hal::twi::Twi as I2c, pac::twi0::frequency::FREQUENCY_A};
+//! # #[cfg(feature = "v2")]
+//! # use microbit::{hal::twim::Twim as I2c, pac::twim0::frequency::FREQUENCY_A};
+//!
+//! let board = Board::take().unwrap();
+//! # #[cfg(feature = "v1")]
+//! # let i2c = I2c::new(board.TWI0, board.i2c.into(), FREQUENCY_A::K100);
+//! # #[cfg(feature = "v2")]
+//! let i2c = I2c::new(board.TWIM0, board.i2c_internal.into(), FREQUENCY_A::K100);
+//!
+//! let mut accelerometer = Accelerometer::new(i2c).unwrap();
+//! let acceleration = accelerometer.acceleration().unwrap();
+//! // about -1000 when lying screen up
+//! let z_mg = acceleration.z;
+//! ```
+
+mod accelerometer;
+mod calibration;
+mod compass;
+mod gesture;
+mod interrupt;
+mod logger;
+mod pedometer;
+
+pub use accelerometer::{Accelerometer, Error, Interrupt, Model, Range};
+pub use calibration::{Calibration, Calibrator, RECORD_LEN, UNIT_SCALE};
+pub use compass::{heading, Compass, Magnetometer};
+pub use gesture::{Gesture, GestureDetector, Gestures};
+pub use interrupt::InterruptAccelerometer;
+pub use logger::{CsvError, MotionLog, Sample, Samples, SAMPLE_LEN};
+pub use pedometer::{Pedometer, DEFAULT_THRESHOLD_MG};
+
+/// A three axis reading of a motion sensor.
+///
+/// The axes and signs are the ones MakeCode uses on every board: an
+/// accelerometer at rest reads `z` close to -1000 mg lying screen up, `x`
+/// close to 1000 mg tilted onto its right edge and `y` close to -1000 mg
+/// standing on its edge connector, logo up.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Vector {
+    /// Component along the X axis.
+    pub x: i32,
+    /// Component along the Y axis.
+    pub y: i32,
+    /// Component along the Z axis.
+    pub z: i32,
+}
+
+impl Vector {
+    /// Create a vector from its components.
+    pub const fn new(x: i32, y: i32, z: i32) -> Self {
+        Self { x, y, z }
+    }
+
+    /// Returns the length of the vector, rounded down.
+    ///
+    /// ```
+    /// # use microbit_common as microbit;
+    /// use microbit::motion::Vector;
+    ///
+    /// assert_eq!(Vector::new(300, -400, 1200).magnitude(), 1300);
+    /// ```
+    pub fn magnitude(&self) -> u32 {
+        let square = |value: i32| i64::from(value).pow(2) as u64;
+        isqrt(square(self.x) + square(self.y) + square(self.z)) as u32
+    }
+}
+
+/// Integer square root, rounded down.
+fn isqrt(x: u64) -> u64 {
+    if x == 0 {
+        return 0;
+    }
+    // start at or above the root and come down
+    let mut root = 1u64 << (64 - x.leading_zeros()).div_ceil(2);
+    loop {
+        let next = (root + x / root) / 2;
+        if next >= root {
+            return root;
+        }
+        root = next;
+    }
+}
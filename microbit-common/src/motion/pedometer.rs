@@ -0,0 +1,217 @@
+//! Step counting, independent of the hardware.
+
+use super::Vector;
+
+/// Default acceleration a step must reach above the average, in milli-g.
+pub const DEFAULT_THRESHOLD_MG: u32 = 150;
+
+/// Shortest time between two steps, in milliseconds: nobody walks or runs
+/// faster than 4 steps a second.
+const MIN_STEP_MS: u32 = 250;
+
+/// Longest time between two steps of the same walk, in milliseconds.
+const MAX_STEP_MS: u32 = 1500;
+
+/// Number of steps in a row before they are counted, so that isolated
+/// bumps are not.
+const STEPS_TO_WALK: u32 = 4;
+
+/// How fast the average acceleration follows the signal, as a shift: the
+/// average moves by `1 / (1 << BASELINE_SHIFT)` of the difference each
+/// sample.
+const BASELINE_SHIFT: u32 = 5;
+
+/// How much the signal is smoothed, as a shift like [`BASELINE_SHIFT`].
+const SMOOTHING_SHIFT: u32 = 2;
+
+/// Fractional bits of the filtered values.
+const FRACTION_BITS: u32 = 8;
+
+/// Counts steps from accelerometer samples.
+///
+/// The pedometer works on the magnitude of the acceleration, so it doesn't
+/// matter how the board is worn. It removes the average, which is gravity,
+/// smooths out the jolts, and counts a step each time the acceleration
+/// peaks more than a threshold above the average. A step only counts as
+/// part of a walk: once 4 steps come in a row, each less than 1.5 seconds
+/// after the previous one, they are all counted, and so are the following
+/// ones.
+///
+/// The pedometer doesn't touch any hardware, so it can be fed recorded
+/// samples on the host, here 10 seconds of a made up walk:
+///
+/// ```
+/// # use microbit_common as microbit;
+/// use microbit::motion::{Pedometer, Vector};
+///
+/// // 50 samples a second, 2 steps a second
+/// let walk = (0..500).map(|i| {
+///     let phase = i as f32 * 2.0 * core::f32::consts::PI * 2.0 / 50.0;
+///     let bounce = (phase.sin() * 400.0) as i32;
+///     Vector::new(100, -1000 + bounce, 150)
+/// });
+///
+/// let mut pedometer = Pedometer::new(50);
+/// // standing still, with some noise
+/// for i in 0..100 {
+///     pedometer.update(Vector::new(0, -1000 + (i % 5) * 20, 0));
+/// }
+/// assert_eq!(pedometer.steps(), 0);
+///
+/// for acceleration in walk {
+///     pedometer.update(acceleration);
+/// }
+/// assert!((19..=20).contains(&pedometer.steps()));
+/// assert!((115..=125).contains(&pedometer.cadence()));
+///
+/// // stopped for a while
+/// for _ in 0..150 {
+///     pedometer.update(Vector::new(100, -1000, 150));
+/// }
+/// assert_eq!(pedometer.cadence(), 0);
+/// ```
+#[derive(Copy, Clone, Debug)]
+pub struct Pedometer {
+    sample_rate_hz: u32,
+    threshold: i32,
+    // average and smoothed magnitudes, with FRACTION_BITS fractional bits
+    baseline: i32,
+    smoothed: i32,
+    settled: bool,
+    // the signal went back below the average since the last step
+    armed: bool,
+    // samples since the last step
+    since_step: u32,
+    // steps in a row, up to STEPS_TO_WALK
+    streak: u32,
+    steps: u32,
+    // average time between steps, with FRACTION_BITS fractional bits
+    interval: u32,
+}
+
+impl Pedometer {
+    /// Create a pedometer for samples taken at `sample_rate_hz`, with the
+    /// [`DEFAULT_THRESHOLD_MG`].
+    pub const fn new(sample_rate_hz: u32) -> Self {
+        Self {
+            sample_rate_hz,
+            threshold: (DEFAULT_THRESHOLD_MG << FRACTION_BITS) as i32,
+            baseline: 0,
+            smoothed: 0,
+            settled: false,
+            armed: false,
+            since_step: u32::MAX,
+            streak: 0,
+            steps: 0,
+            interval: 0,
+        }
+    }
+
+    /// Set the acceleration a step must reach above the average, in
+    /// milli-g.
+    ///
+    /// A lower threshold makes the pedometer more sensitive, to count gentle
+    /// steps or with the board worn on the wrist, but it may count steps
+    /// that weren't.
+    pub fn set_threshold_mg(&mut self, threshold_mg: u32) {
+        self.threshold =
+            (threshold_mg.min(u32::MAX >> (FRACTION_BITS + 1)) << FRACTION_BITS) as i32;
+    }
+
+    /// Returns the acceleration a step must reach above the average, in
+    /// milli-g.
+    pub fn threshold_mg(&self) -> u32 {
+        (self.threshold >> FRACTION_BITS) as u32
+    }
+
+    /// Returns the number of steps counted.
+    pub fn steps(&self) -> u32 {
+        self.steps
+    }
+
+    /// Start counting steps from zero again.
+    pub fn reset_steps(&mut self) {
+        self.steps = 0;
+    }
+
+    /// Returns the number of steps per minute, or `0` when not walking.
+    pub fn cadence(&self) -> u32 {
+        if !self.walking() || self.interval == 0 {
+            return 0;
+        }
+        let per_minute = (u64::from(self.sample_rate_hz) * 60) << FRACTION_BITS;
+        ((per_minute + u64::from(self.interval) / 2) / u64::from(self.interval)) as u32
+    }
+
+    /// Feed the next acceleration, in milli-g.
+    ///
+    /// Returns the number of steps counted with this sample: usually none,
+    /// one, or the first steps of a walk all at once.
+    pub fn update(&mut self, acceleration: Vector) -> u32 {
+        let magnitude = (acceleration
+            .magnitude()
+            .min(i32::MAX as u32 >> FRACTION_BITS) as i32)
+            << FRACTION_BITS;
+        if !self.settled {
+            self.baseline = magnitude;
+            self.smoothed = magnitude;
+            self.settled = true;
+        }
+        self.baseline += (magnitude - self.baseline) >> BASELINE_SHIFT;
+        self.smoothed += (magnitude - self.smoothed) >> SMOOTHING_SHIFT;
+        let signal = self.smoothed - self.baseline;
+
+        self.since_step = self.since_step.saturating_add(1);
+        if signal < 0 {
+            self.armed = true;
+        }
+        if !self.armed || signal < self.threshold || self.since_step < self.samples(MIN_STEP_MS) {
+            return 0;
+        }
+
+        // a step
+        self.armed = false;
+        let interval = self.since_step;
+        self.since_step = 0;
+        if interval > self.samples(MAX_STEP_MS) {
+            // the first step of a new walk
+            self.streak = 1;
+            self.interval = 0;
+            return 0;
+        }
+        self.interval = if self.interval == 0 {
+            interval << FRACTION_BITS
+        } else {
+            // average over the last few steps
+            (self.interval * 3 + (interval << FRACTION_BITS)) / 4
+        };
+        if self.streak < STEPS_TO_WALK {
+            self.streak += 1;
+            if self.streak < STEPS_TO_WALK {
+                return 0;
+            }
+            self.steps += STEPS_TO_WALK;
+            return STEPS_TO_WALK;
+        }
+        self.steps += 1;
+        1
+    }
+
+    /// Returns `true` while steps come in a row.
+    fn walking(&self) -> bool {
+        self.streak >= STEPS_TO_WALK && self.since_step <= self.samples(MAX_STEP_MS)
+    }
+
+    /// Returns a duration in samples.
+    fn samples(&self, duration_ms: u32) -> u32 {
+        (self.sample_rate_hz * duration_ms / 1000).max(1)
+    }
+}
+
+impl Default for Pedometer {
+    /// A pedometer for the 50Hz samples of the
+    /// [`Accelerometer`](super::Accelerometer).
+    fn default() -> Self {
+        Self::new(50)
+    }
+}
@@ -0,0 +1,150 @@
+//! The built-in melodies of MicroPython for the micro:bit.
+//!
+//! These are the tunes of MicroPython's `music` module, with the same names,
+//! ready to be played by a [`MelodyPlayer`](super::MelodyPlayer):
+//!
+//! ```no_run
+//! # use microbit_common as microbit;
+//! # use microbit::music::{MelodyPlayer, ToneOutput};
+//! # fn play<O: ToneOutput>(player: &mut MelodyPlayer<'_, O>) {
+//! use microbit::music::melodies;
+//!
+//! player.play(melodies::POWER_UP).unwrap();
+//! # }
+//! ```
+//!
+//! They are all played at the default tempo of 4 ticks per beat at 120 beats
+//! per minute, which [`Melody::with_tempo`] and
+//! [`MelodyPlayer::set_bpm`](super::MelodyPlayer::set_bpm) can change.
+//!
+//! ```
+//! # use microbit_common as microbit;
+//! use microbit::music::melodies;
+//!
+//! for melody in melodies::ALL {
+//!     assert!(melody.validate().is_ok());
+//! }
+//! assert_eq!(melodies::BA_DING.duration_ms(), Ok(500));
+//! ```
+//!
+//! The melodies come from [MicroPython for the micro:bit][micropython], where
+//! they are released under the MIT license.
+//!
+//! [micropython]: https://github.com/bbcmicrobit/micropython
+
+use super::Melody;
+
+/// Beethoven's Fifth Symphony in C minor, opening.
+pub const DADADADUM: Melody<'static> = Melody::new("r4:2 g g g eb:8 r:2 f f f d:8");
+
+/// The opening of Scott Joplin's ragtime "The Entertainer".
+pub const ENTERTAINER: Melody<'static> =
+    Melody::new("d4:1 d# e c5:2 e4:1 c5:2 e4:1 c5:3 c:1 d d# e c d e:2 b4:1 d5:2 c:4");
+
+/// The opening of the first Prelude in C major of J.S. Bach's Well-Tempered Clavier.
+pub const PRELUDE: Melody<'static> = Melody::new(
+    "c4:1 e g c5 e g4 c5 e c4 e g c5 e g4 c5 e c4 d g d5 f g4 d5 f c4 d g d5 f g4 d5 \
+     f b3 d4 g d5 f g4 d5 f b3 d4 g d5 f g4 d5 f c4 e g c5 e g4 c5 e c4 e g c5 e g4 \
+     c5 e",
+);
+
+/// The "Ode to Joy" theme from Beethoven's Ninth Symphony in D minor.
+pub const ODE: Melody<'static> =
+    Melody::new("e4 e f g g f e d c c d e e:6 d:2 d:8 e:4 e f g g f e d c c d e d:6 c:2 c:8");
+
+/// The Nyan Cat theme.
+pub const NYAN: Melody<'static> = Melody::new(
+    "f#5:2 g# c#:1 d#:2 b4:1 d5:1 c# b4:2 b c#5 d d:1 c# b4:1 c#5:1 d# f# g# d# f# c# \
+     d b4 c#5 b4 d#5:2 f# g#:1 d# f# c# d# b4 d5 d# d c# b4 c#5 d:2 b4:1 c#5 d# f# c# \
+     d c# b4 c#5:2 b4 c#5 b4 f#:1 g# b:2 f#:1 g# b c#5 d# b4 e5 d# e f# b4:2 b f#:1 \
+     g# b f# e5 d# c# b4 f# d# e f# b:2 f#:1 g# b:2 f#:1 g# b b c#5 d# b4 f# g# f# \
+     b:2 b:1 a# b f# g# b e5 d# e f# b4:2 c#5",
+);
+
+/// Something that sounds like a mobile phone ringtone, to indicate an incoming message.
+pub const RINGTONE: Melody<'static> = Melody::new("c4:1 d e:2 g d:1 e f:2 a e:1 f g:2 b c5:4");
+
+/// A funky bass line for secret agents and criminal masterminds.
+pub const FUNK: Melody<'static> =
+    Melody::new("c2:2 c d# c:1 f:2 c:1 f:2 f# g c c g c:1 f#:2 c:1 f#:2 f d#");
+
+/// A boogie-woogie 12-bar blues walking bass.
+pub const BLUES: Melody<'static> = Melody::new(
+    "c2:2 e g a a# a g e c2:2 e g a a# a g e f a c3 d d# d c a2 c2:2 e g a a# a g e g \
+     b d3 f f2 a c3 d# c2:2 e g e g f e d",
+);
+
+/// "Happy Birthday to You".
+pub const BIRTHDAY: Melody<'static> = Melody::new(
+    "c4:3 c:1 d:4 c:4 f e:8 c:3 c:1 d:4 c:4 g f:8 c:3 c:1 c5:4 a4 f e d a#:3 a#:1 a:4 \
+     f g f:8",
+);
+
+/// The bridal chorus from Wagner's opera "Lohengrin".
+pub const WEDDING: Melody<'static> =
+    Melody::new("c4:4 f:3 f:1 f:8 c:4 g:3 e:1 f:8 c:4 f:3 a:1 c5:4 a4:3 f:1 f:4 e:3 f:1 g:8");
+
+/// The "funeral march" from Chopin's Piano Sonata No. 2 in B-flat minor.
+pub const FUNERAL: Melody<'static> = Melody::new("c3:4 c:3 c:1 c:4 d#:3 d:1 d:3 c:1 c:3 b2:1 c3:4");
+
+/// A fun fragment that signifies a joke has been made.
+pub const PUNCHLINE: Melody<'static> = Melody::new("c4:3 g3:1 f# g g#:3 g r b c4");
+
+/// John Philip Sousa's march "Liberty Bell", the theme of "Monty Python's Flying Circus".
+pub const PYTHON: Melody<'static> = Melody::new(
+    "d5:1 b4 r b b a# b g5 r d d r b4 c5 r c c r d e:5 c:1 a4 r a a g# a f#5 r e e r \
+     c b4 r b b r c5 d:5 d:1 b4 r b b a# b b5 r g g r d c# r a a r a a:5 g:1 f#:2 a:1 \
+     a g# a e:2 a:1 a g# a d r c# d r c# d:2 r:3",
+);
+
+/// The entrance of a silent film villain.
+pub const BADDY: Melody<'static> = Melody::new("c3:3 r d:2 d# r c r f#:8");
+
+/// A silent film chase scene.
+pub const CHASE: Melody<'static> = Melody::new(
+    "a4:1 b c5 b4 a:2 r a:1 b c5 b4 a:2 r a:2 e5 d# e f e d# e b4:1 c5 d c b4:2 r b:1 \
+     c5 d c b4:2 r b:2 e5 d# e f e d# e",
+);
+
+/// A short signal to indicate something has happened.
+pub const BA_DING: Melody<'static> = Melody::new("b5:1 e6:3");
+
+/// A very sad trombone.
+pub const WAWAWAWAA: Melody<'static> = Melody::new("e3:3 r:1 d#:3 r:1 d:4 r:1 c#:8");
+
+/// For use in a game, indicating upward movement.
+pub const JUMP_UP: Melody<'static> = Melody::new("c5:1 d e f g");
+
+/// For use in a game, indicating downward movement.
+pub const JUMP_DOWN: Melody<'static> = Melody::new("g5:1 f e d c");
+
+/// A fanfare to indicate an achievement unlocked.
+pub const POWER_UP: Melody<'static> = Melody::new("g4:1 c5 e g:2 e:1 g:3");
+
+/// A sad fanfare to indicate an achievement lost.
+pub const POWER_DOWN: Melody<'static> = Melody::new("g5:1 d# c g4:2 b:1 c5:3");
+
+/// All built-in melodies, in the order MicroPython lists them.
+pub const ALL: [Melody<'static>; 21] = [
+    DADADADUM,
+    ENTERTAINER,
+    PRELUDE,
+    ODE,
+    NYAN,
+    RINGTONE,
+    FUNK,
+    BLUES,
+    BIRTHDAY,
+    WEDDING,
+    FUNERAL,
+    PUNCHLINE,
+    PYTHON,
+    BADDY,
+    CHASE,
+    BA_DING,
+    WAWAWAWAA,
+    JUMP_UP,
+    JUMP_DOWN,
+    POWER_UP,
+    POWER_DOWN,
+];
@@ -0,0 +1,56 @@
+//! Melodies in RTTTL and MicroPython/MakeCode notation.
+//!
+//! A [`Melody`] wraps a string of notes, either in the MicroPython/MakeCode
+//! notation (`"C4:4 D E F"`) or as an RTTTL ringtone. A [`MelodyPlayer`]
+//! plays it on any [`ToneOutput`]:
+//!
+//! - the micro:bit V2 on-board [`Speaker`](crate::speaker::Speaker)
+//! - a [`PinTone`], which plays on any GPIO pin such as the edge connector
+//!   pads, on both boards
+//!
+//! # Example
+//!
+//! ```no_run
+//! # use microbit_common as microbit;
+//! use embedded_hal::delay::DelayNs;
+//! use microbit::{
+//!     hal::{gpio::Level, gpiote::Gpiote, ppi, Timer},
+//!     music::{Melody, MelodyEvent, MelodyPlayer, PinTone},
+//!     Board,
+//! };
+//!
+//! const TICK_MS: u32 = 10;
+//!
+//! let board = Board::take().unwrap();
+//! let mut timer = Timer::new(board.TIMER0);
+//! let gpiote = Gpiote::new(board.GPIOTE);
+//! let ppi = ppi::Parts::new(board.PPI);
+//! let pin = board.edge.e00.into_push_pull_output(Level::Low).degrade();
+//! let tone = PinTone::new(board.TIMER1, gpiote.channel0(), ppi.ppi0, pin);
+//!
+//! let mut player = MelodyPlayer::new(tone);
+//! let melody = Melody::from_rtttl("scale:d=8,o=5,b=160:c,d,e,f,g,a,b,4c6").unwrap();
+//! player.play(melody).unwrap();
+//! loop {
+//!     timer.delay_ms(TICK_MS);
+//!     if player.tick(TICK_MS) == Some(MelodyEvent::Finished) {
+//!         break;
+//!     }
+//! }
+//! ```
+//!
+//! The tunes built into MicroPython, such as
+//! [`melodies::DADADADUM`], are in the [`melodies`]
+//! module.
+//!
+//! Parsing doesn't touch any hardware, so [`Melody`] and [`MelodyPlayer`] can
+//! be tested on the host.
+
+pub mod melodies;
+mod notation;
+mod pin;
+mod player;
+
+pub use notation::{Melody, Note, Notes, ParseError, Tempo};
+pub use pin::{PinTone, MAX_FREQUENCY_HZ, MIN_FREQUENCY_HZ};
+pub use player::{MelodyEvent, MelodyPlayer, ToneOutput, ARTICULATION_MS};
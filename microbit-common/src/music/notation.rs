@@ -0,0 +1,450 @@
+//! Parsing of RTTTL and MicroPython/MakeCode melody notation.
+//!
+//! Melodies are parsed lazily while they are played, so nothing needs to be
+//! allocated and a melody can be a `const`. Parsing only depends on `core`,
+//! and the examples below run on the host as doc tests.
+
+use core::{convert::TryFrom, fmt};
+
+/// Frequencies of the notes of octave 8, from C to B, in Hz. Lower octaves are
+/// derived by halving.
+const OCTAVE_8_HZ: [u32; 12] = [
+    4186, 4435, 4699, 4978, 5274, 5588, 5920, 6272, 6645, 7040, 7459, 7902,
+];
+
+/// Highest supported octave.
+const MAX_OCTAVE: u8 = 8;
+
+/// Ticks per beat used for RTTTL melodies, so a dotted 32nd note is a whole
+/// number of ticks.
+const RTTTL_TICKS_PER_BEAT: u16 = 16;
+
+/// The speed a melody is played at.
+///
+/// Note durations are given in ticks; a tick lasts `1 / ticks_per_beat` of a
+/// beat.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Tempo {
+    /// Number of ticks in one beat.
+    pub ticks_per_beat: u16,
+    /// Beats per minute.
+    pub bpm: u16,
+}
+
+impl Tempo {
+    /// The MicroPython default tempo: 4 ticks per beat at 120 beats per minute.
+    pub const DEFAULT: Tempo = Tempo::new(4, 120);
+
+    /// Create a tempo.
+    pub const fn new(ticks_per_beat: u16, bpm: u16) -> Self {
+        Self {
+            ticks_per_beat,
+            bpm,
+        }
+    }
+
+    /// Returns the duration of `ticks` ticks at this tempo, in milliseconds.
+    ///
+    /// ```
+    /// # use microbit_common as microbit;
+    /// use microbit::music::Tempo;
+    ///
+    /// assert_eq!(Tempo::DEFAULT.duration_ms(4), 500);
+    /// ```
+    pub const fn duration_ms(&self, ticks: u16) -> u32 {
+        let ticks_per_minute = self.ticks_per_beat as u32 * self.bpm as u32;
+        match (ticks as u32 * 60_000).checked_div(ticks_per_minute) {
+            Some(duration_ms) => duration_ms,
+            None => 0,
+        }
+    }
+}
+
+impl Default for Tempo {
+    fn default() -> Self {
+        Tempo::DEFAULT
+    }
+}
+
+/// A single note, or a rest, of a melody.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Note {
+    /// Frequency of the note in Hz, `0` for a rest.
+    pub frequency_hz: u16,
+    /// Duration of the note in ticks of the melody's [`Tempo`].
+    pub ticks: u16,
+}
+
+impl Note {
+    /// Returns `true` if this is a rest.
+    pub const fn is_rest(&self) -> bool {
+        self.frequency_hz == 0
+    }
+}
+
+/// An error found while parsing a melody.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ParseError {
+    /// The RTTTL header (`name:d=4,o=5,b=63:`) is malformed.
+    Header,
+    /// The note starting at this byte offset is malformed.
+    Note(usize),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::Header => f.write_str("invalid RTTTL header"),
+            ParseError::Note(offset) => write!(f, "invalid note at offset {}", offset),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Format {
+    MicroPython,
+    Rtttl,
+}
+
+/// A melody in RTTTL or MicroPython/MakeCode notation.
+///
+/// # MicroPython/MakeCode notation
+///
+/// Notes are separated by spaces (or commas) and written as
+/// `NOTE[#|b][OCTAVE][:TICKS]`, for example `c4:4`, `f#`, `eb5:2` or `r:8`
+/// for a rest. The octave and duration carry over to the following notes when
+/// they are left out, starting from octave 4 and 4 ticks. This is the format
+/// used by MicroPython's `music.play` and MakeCode's melody strings.
+///
+/// ```
+/// # use microbit_common as microbit;
+/// use microbit::music::{Melody, Note};
+///
+/// let melody = Melody::new("C4:4 D E F:8");
+/// let notes: Result<Vec<Note>, _> = melody.notes().collect();
+/// assert_eq!(
+///     notes.unwrap(),
+///     [
+///         Note { frequency_hz: 262, ticks: 4 },
+///         Note { frequency_hz: 294, ticks: 4 },
+///         Note { frequency_hz: 330, ticks: 4 },
+///         Note { frequency_hz: 349, ticks: 8 },
+///     ]
+/// );
+/// ```
+///
+/// # RTTTL
+///
+/// Ring Tone Text Transfer Language melodies have a header with a name, the
+/// default duration, octave and beats per minute, followed by the notes
+/// written as `[DURATION]NOTE[#][.][OCTAVE][.]`:
+///
+/// ```
+/// # use microbit_common as microbit;
+/// use microbit::music::{Melody, Note, Tempo};
+///
+/// let melody = Melody::from_rtttl("tune:d=4,o=4,b=120:8a,p,c5.").unwrap();
+/// assert_eq!(melody.tempo(), Tempo::new(16, 120));
+/// let notes: Result<Vec<Note>, _> = melody.notes().collect();
+/// assert_eq!(
+///     notes.unwrap(),
+///     [
+///         Note { frequency_hz: 440, ticks: 8 },
+///         Note { frequency_hz: 0, ticks: 16 },
+///         Note { frequency_hz: 523, ticks: 24 },
+///     ]
+/// );
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Melody<'a> {
+    format: Format,
+    notes: &'a str,
+    // offset of `notes` in the original string, for error reporting
+    offset: usize,
+    tempo: Tempo,
+    octave: u8,
+    ticks: u16,
+}
+
+impl<'a> Melody<'a> {
+    /// Create a melody from notes in MicroPython/MakeCode notation, played at
+    /// [`Tempo::DEFAULT`].
+    ///
+    /// The notes are checked when they are played; use
+    /// [`validate`](Melody::validate) to check them upfront.
+    pub const fn new(notes: &'a str) -> Self {
+        Self {
+            format: Format::MicroPython,
+            notes,
+            offset: 0,
+            tempo: Tempo::DEFAULT,
+            octave: 4,
+            ticks: 4,
+        }
+    }
+
+    /// Create a melody from an RTTTL ringtone.
+    ///
+    /// The header is parsed immediately; the notes are checked when they are
+    /// played, or by [`validate`](Melody::validate).
+    pub fn from_rtttl(rtttl: &'a str) -> Result<Self, ParseError> {
+        let mut sections = rtttl.splitn(3, ':');
+        let (_name, header, notes) = match (sections.next(), sections.next(), sections.next()) {
+            (Some(name), Some(header), Some(notes)) => (name, header, notes),
+            _ => return Err(ParseError::Header),
+        };
+
+        // defaults from the RTTTL specification
+        let mut duration = 4;
+        let mut octave = 6;
+        let mut bpm = 63;
+        for setting in header.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+            let (key, value) = setting.split_once('=').ok_or(ParseError::Header)?;
+            let value: u16 = value.trim().parse().map_err(|_| ParseError::Header)?;
+            match key.trim() {
+                "d" | "D" => duration = value,
+                "o" | "O" => octave = value,
+                "b" | "B" => bpm = value,
+                _ => return Err(ParseError::Header),
+            }
+        }
+
+        let ticks = rtttl_ticks(duration, false).ok_or(ParseError::Header)?;
+        if octave > u16::from(MAX_OCTAVE) || bpm == 0 {
+            return Err(ParseError::Header);
+        }
+        Ok(Self {
+            format: Format::Rtttl,
+            notes,
+            offset: rtttl.len() - notes.len(),
+            tempo: Tempo::new(RTTTL_TICKS_PER_BEAT, bpm),
+            octave: octave as u8,
+            ticks,
+        })
+    }
+
+    /// Returns this melody played at a different tempo.
+    pub const fn with_tempo(self, tempo: Tempo) -> Self {
+        Self { tempo, ..self }
+    }
+
+    /// Returns the tempo of this melody.
+    pub const fn tempo(&self) -> Tempo {
+        self.tempo
+    }
+
+    /// Returns an iterator over the notes of this melody.
+    pub fn notes(&self) -> Notes<'a> {
+        Notes {
+            format: self.format,
+            rest: self.notes,
+            offset: self.offset,
+            octave: self.octave,
+            ticks: self.ticks,
+        }
+    }
+
+    /// Check that all notes of this melody can be parsed.
+    pub fn validate(&self) -> Result<(), ParseError> {
+        self.notes().try_for_each(|note| note.map(drop))
+    }
+
+    /// Returns the total duration of this melody, in milliseconds.
+    pub fn duration_ms(&self) -> Result<u32, ParseError> {
+        self.notes().try_fold(0u32, |total, note| {
+            Ok(total.saturating_add(self.tempo.duration_ms(note?.ticks)))
+        })
+    }
+}
+
+/// Iterator over the notes of a [`Melody`], returned by [`Melody::notes`].
+#[derive(Clone, Debug)]
+pub struct Notes<'a> {
+    format: Format,
+    rest: &'a str,
+    offset: usize,
+    octave: u8,
+    ticks: u16,
+}
+
+impl Iterator for Notes<'_> {
+    type Item = Result<Note, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let is_separator = |c: char| c.is_whitespace() || c == ',';
+        let trimmed = self.rest.trim_start_matches(is_separator);
+        self.offset += self.rest.len() - trimmed.len();
+        if trimmed.is_empty() {
+            self.rest = trimmed;
+            return None;
+        }
+
+        let end = trimmed.find(is_separator).unwrap_or(trimmed.len());
+        let (token, rest) = trimmed.split_at(end);
+        let offset = self.offset;
+        self.rest = rest;
+        self.offset += end;
+
+        let note = match self.format {
+            Format::MicroPython => self.parse_micropython(token.as_bytes()),
+            Format::Rtttl => self.parse_rtttl(token.as_bytes()),
+        };
+        if note.is_none() {
+            // stop at the first error
+            self.rest = "";
+        }
+        Some(note.ok_or(ParseError::Note(offset)))
+    }
+}
+
+impl Notes<'_> {
+    /// Parse `NOTE[#|b][OCTAVE][:TICKS]`, updating the current octave and
+    /// duration.
+    fn parse_micropython(&mut self, token: &[u8]) -> Option<Note> {
+        let mut bytes = Cursor(token);
+        let semitone = bytes.semitone()?;
+        let accidental = match bytes.peek() {
+            Some(b'#') => 1,
+            Some(b'b') => -1,
+            _ => 0,
+        };
+        if accidental != 0 {
+            bytes.next();
+        }
+        if let Some(octave) = bytes.number() {
+            self.octave = u8::try_from(octave).ok()?;
+        }
+        if bytes.peek() == Some(b':') {
+            bytes.next();
+            self.ticks = bytes.number().filter(|&ticks| ticks > 0)?;
+        }
+        if !bytes.is_empty() {
+            return None;
+        }
+        note(semitone, accidental, self.octave, self.ticks)
+    }
+
+    /// Parse `[DURATION]NOTE[#][.][OCTAVE][.]`.
+    fn parse_rtttl(&mut self, token: &[u8]) -> Option<Note> {
+        let mut bytes = Cursor(token);
+        let duration = bytes.number();
+        let semitone = bytes.semitone()?;
+        let accidental = if bytes.peek() == Some(b'#') {
+            bytes.next();
+            1
+        } else {
+            0
+        };
+        let mut dotted = bytes.peek() == Some(b'.');
+        if dotted {
+            bytes.next();
+        }
+        let octave = match bytes.number() {
+            Some(octave) => u8::try_from(octave).ok()?,
+            None => self.octave,
+        };
+        if bytes.peek() == Some(b'.') {
+            bytes.next();
+            dotted = true;
+        }
+        if !bytes.is_empty() {
+            return None;
+        }
+        let ticks = match duration {
+            Some(duration) => rtttl_ticks(duration, dotted)?,
+            None if dotted => self.ticks + self.ticks / 2,
+            None => self.ticks,
+        };
+        note(semitone, accidental, octave, ticks)
+    }
+}
+
+/// Returns the number of ticks of an RTTTL note of the given duration (`4`
+/// for a quarter note), if it is valid.
+fn rtttl_ticks(duration: u16, dotted: bool) -> Option<u16> {
+    let ticks = match duration {
+        1 | 2 | 4 | 8 | 16 | 32 => 4 * RTTTL_TICKS_PER_BEAT / duration,
+        _ => return None,
+    };
+    Some(if dotted { ticks + ticks / 2 } else { ticks })
+}
+
+/// Build a note from a semitone within the octave (`None` for a rest), an
+/// accidental of -1, 0 or 1 and an octave.
+fn note(semitone: Option<u8>, accidental: i8, octave: u8, ticks: u16) -> Option<Note> {
+    if octave > MAX_OCTAVE {
+        return None;
+    }
+    let semitone = match semitone {
+        Some(semitone) => semitone,
+        None => {
+            return Some(Note {
+                frequency_hz: 0,
+                ticks,
+            })
+        }
+    };
+    // sharps and flats may cross into the neighbouring octave, e.g. `cb`
+    let index = i16::from(octave) * 12 + i16::from(semitone) + i16::from(accidental);
+    let octave = u8::try_from(index / 12).ok().filter(|&o| o <= MAX_OCTAVE)?;
+    let frequency = OCTAVE_8_HZ[usize::try_from(index % 12).ok()?];
+    let shift = MAX_OCTAVE - octave;
+    let frequency = if shift == 0 {
+        frequency
+    } else {
+        (frequency + (1 << (shift - 1))) >> shift
+    };
+    Some(Note {
+        frequency_hz: frequency as u16,
+        ticks,
+    })
+}
+
+/// Minimal cursor over the bytes of a note.
+struct Cursor<'a>(&'a [u8]);
+
+impl Cursor<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.0.first().copied()
+    }
+
+    fn next(&mut self) -> Option<u8> {
+        let (&first, rest) = self.0.split_first()?;
+        self.0 = rest;
+        Some(first)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Parse a decimal number, if there is one.
+    fn number(&mut self) -> Option<u16> {
+        let digits = self.0.iter().take_while(|b| b.is_ascii_digit()).count();
+        if digits == 0 {
+            return None;
+        }
+        let (number, rest) = self.0.split_at(digits);
+        self.0 = rest;
+        number.iter().try_fold(0u16, |acc, digit| {
+            acc.checked_mul(10)?.checked_add(u16::from(digit - b'0'))
+        })
+    }
+
+    /// Parse a note letter, returning its semitone within the octave or
+    /// `Some(None)` for a rest.
+    fn semitone(&mut self) -> Option<Option<u8>> {
+        let semitone = match self.next()?.to_ascii_lowercase() {
+            b'c' => Some(0),
+            b'd' => Some(2),
+            b'e' => Some(4),
+            b'f' => Some(5),
+            b'g' => Some(7),
+            b'a' => Some(9),
+            b'b' | b'h' => Some(11),
+            b'r' | b'p' => None,
+            _ => return None,
+        };
+        Some(semitone)
+    }
+}
@@ -0,0 +1,165 @@
+//! Tones on any GPIO pin, generated in hardware with TIMER, GPIOTE and PPI.
+
+use super::player::ToneOutput;
+use crate::{
+    hal::{
+        gpio::{Output, Pin, PushPull},
+        gpiote::{GpioteChannel, TaskOutPolarity},
+        ppi::ConfigurablePpi,
+        timer::Instance,
+    },
+    pac,
+};
+
+/// Frequency of the timer clock, in Hz.
+const TIMER_CLOCK_HZ: u32 = 1_000_000;
+
+/// Lowest tone frequency that fits in the 16 bit timer, in Hz.
+pub const MIN_FREQUENCY_HZ: u32 = 8;
+
+/// Highest tone frequency, in Hz.
+pub const MAX_FREQUENCY_HZ: u32 = 20_000;
+
+/// A square wave tone on a GPIO pin, such as one of the edge connector pads.
+///
+/// This works on both boards, including the micro:bit V1 which has no PWM
+/// peripheral: a TIMER compare event toggles the pin through a PPI channel
+/// and a GPIOTE task, so once a tone is started it plays without any CPU
+/// involvement.
+///
+/// The timer is set to 16-bit mode with a 1 MHz clock and is cleared by its
+/// own CC0 compare event, every half period of the tone.
+///
+/// # Example
+///
+/// ```no_run
+/// # use microbit_common as microbit;
+/// use microbit::{
+///     hal::{gpio::Level, gpiote::Gpiote, ppi},
+///     music::{Melody, MelodyPlayer, PinTone},
+///     Board,
+/// };
+///
+/// let board = Board::take().unwrap();
+/// let gpiote = Gpiote::new(board.GPIOTE);
+/// let ppi = ppi::Parts::new(board.PPI);
+/// let pin = board.edge.e00.into_push_pull_output(Level::Low).degrade();
+///
+/// let tone = PinTone::new(board.TIMER1, gpiote.channel0(), ppi.ppi0, pin);
+/// let mut player = MelodyPlayer::new(tone);
+/// player.play(Melody::new("c4:4 e g c5:8")).unwrap();
+/// ```
+pub struct PinTone<'a, T: Instance, P: ConfigurablePpi> {
+    timer: T,
+    channel: GpioteChannel<'a>,
+    ppi: P,
+    pin: Pin<Output<PushPull>>,
+}
+
+impl<'a, T: Instance, P: ConfigurablePpi> PinTone<'a, T, P> {
+    /// Create a tone generator on `pin`, using a timer, a GPIOTE channel and
+    /// a PPI channel.
+    ///
+    /// The pin is handed over to the GPIOTE channel, which drives it until
+    /// [`free`](Self::free) gives it back.
+    pub fn new(
+        timer: T,
+        channel: GpioteChannel<'a>,
+        mut ppi: P,
+        pin: Pin<Output<PushPull>>,
+    ) -> Self {
+        init_task(&channel, &pin);
+
+        let timer0 = timer.as_timer0();
+        timer0.tasks_stop.write(|w| unsafe { w.bits(1) });
+        timer0.tasks_clear.write(|w| unsafe { w.bits(1) });
+        timer0.mode.write(|w| w.mode().timer());
+        // set as 16 bits
+        timer0.bitmode.write(|w| w.bitmode()._16bit());
+        // set frequency to 1MHz
+        timer0.prescaler.write(|w| unsafe { w.bits(4) });
+        // restart the count at every toggle
+        timer0.shorts.write(|w| w.compare0_clear().enabled());
+
+        ppi.set_event_endpoint(&timer0.events_compare[0]);
+        ppi.set_task_endpoint(channel.task_out());
+        ppi.enable();
+
+        Self {
+            timer,
+            channel,
+            ppi,
+            pin,
+        }
+    }
+
+    /// Start playing a tone of `freq_hz` until [`PinTone::stop`] is called or
+    /// another tone is started.
+    ///
+    /// The frequency is clamped to [`MIN_FREQUENCY_HZ`]..=[`MAX_FREQUENCY_HZ`].
+    pub fn start(&mut self, freq_hz: u32) {
+        let freq_hz = freq_hz.clamp(MIN_FREQUENCY_HZ, MAX_FREQUENCY_HZ);
+        let half_period = TIMER_CLOCK_HZ / 2 / freq_hz;
+
+        let timer0 = self.timer.as_timer0();
+        timer0.tasks_stop.write(|w| unsafe { w.bits(1) });
+        timer0.tasks_clear.write(|w| unsafe { w.bits(1) });
+        timer0.cc[0].write(|w| unsafe { w.bits(half_period) });
+        timer0.tasks_start.write(|w| unsafe { w.bits(1) });
+    }
+
+    /// Stop the tone, driving the pin low.
+    pub fn stop(&mut self) {
+        self.timer
+            .as_timer0()
+            .tasks_stop
+            .write(|w| unsafe { w.bits(1) });
+        // configuring the task again sets the pin to its initial level, low,
+        // instead of the level of the last toggle
+        init_task(&self.channel, &self.pin);
+    }
+
+    /// Stop the tone, disconnect the pin from the GPIOTE channel and give
+    /// back the timer, the GPIOTE channel, the PPI channel and the pin.
+    pub fn free(mut self) -> (T, GpioteChannel<'a>, P, Pin<Output<PushPull>>) {
+        self.stop();
+        self.ppi.disable();
+
+        // the hal can't disable a GPIOTE channel: find its CONFIG register
+        // from its OUT task
+        // Safety: only the register of the channel owned by `self` is written
+        let gpiote = unsafe { &*pac::GPIOTE::ptr() };
+        let task_out = self.channel.task_out();
+        if let Some((_, config)) = gpiote
+            .tasks_out
+            .iter()
+            .zip(gpiote.config.iter())
+            .find(|(task, _)| core::ptr::eq(*task, task_out))
+        {
+            config.reset();
+        }
+        (self.timer, self.channel, self.ppi, self.pin)
+    }
+}
+
+/// Configure the GPIOTE `channel` to toggle `pin`, starting low.
+fn init_task(channel: &GpioteChannel<'_>, pin: &Pin<Output<PushPull>>) {
+    // the hal keeps the pin it configures the channel with, give it a copy
+    // so the pin can be given back
+    // Safety: the copy is dropped once the channel is configured
+    let task_pin = unsafe { Pin::<Output<PushPull>>::from_psel_bits(pin.psel_bits()) };
+    channel
+        .output_pin(task_pin)
+        .task_out_polarity(TaskOutPolarity::Toggle)
+        .init_low();
+}
+
+impl<T: Instance, P: ConfigurablePpi> ToneOutput for PinTone<'_, T, P> {
+    fn start_tone(&mut self, freq_hz: u32) {
+        self.start(freq_hz);
+    }
+
+    fn stop_tone(&mut self) {
+        self.stop();
+    }
+}
@@ -0,0 +1,199 @@
+//! Playing melodies on a tone output.
+
+use super::notation::{Melody, Note, Notes, ParseError, Tempo};
+
+/// Silence between two notes, in milliseconds, so repeated notes can be told
+/// apart.
+pub const ARTICULATION_MS: u32 = 10;
+
+/// Something that can play a square wave tone, such as the V2
+/// [`Speaker`](crate::speaker::Speaker) or a [`PinTone`](super::PinTone).
+pub trait ToneOutput {
+    /// Start playing a tone of `freq_hz` until [`stop_tone`](Self::stop_tone)
+    /// is called or another tone is started.
+    fn start_tone(&mut self, freq_hz: u32);
+
+    /// Stop the tone currently playing, if any.
+    fn stop_tone(&mut self);
+}
+
+/// Events reported by [`MelodyPlayer::tick`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MelodyEvent {
+    /// The last note of a looping melody ended and the melody started again.
+    Looped,
+    /// The last note of the melody ended and the player is now idle.
+    Finished,
+}
+
+/// Plays a [`Melody`] on a [`ToneOutput`].
+///
+/// The player does not keep time itself: call [`tick`](Self::tick)
+/// regularly, for example from a timer interrupt or the main loop, with the
+/// number of milliseconds elapsed since the previous call.
+///
+/// ```
+/// # use microbit_common as microbit;
+/// use microbit::music::{Melody, MelodyEvent, MelodyPlayer, ToneOutput};
+///
+/// /// Records the tones instead of playing them.
+/// #[derive(Default)]
+/// struct Recorder(Vec<Option<u32>>);
+///
+/// impl ToneOutput for Recorder {
+///     fn start_tone(&mut self, freq_hz: u32) {
+///         self.0.push(Some(freq_hz));
+///     }
+///     fn stop_tone(&mut self) {
+///         self.0.push(None);
+///     }
+/// }
+///
+/// let mut player = MelodyPlayer::new(Recorder::default());
+/// // two quarter notes, 500ms each at the default tempo
+/// player.play(Melody::new("a4 r")).unwrap();
+///
+/// assert_eq!(player.tick(490), None);
+/// assert_eq!(player.tick(500), None);
+/// assert_eq!(player.tick(20), Some(MelodyEvent::Finished));
+/// assert!(!player.is_playing());
+/// assert_eq!(player.free().0, [Some(440), None, None, None]);
+/// ```
+pub struct MelodyPlayer<'a, O: ToneOutput> {
+    output: O,
+    melody: Option<Melody<'a>>,
+    notes: Option<Notes<'a>>,
+    tempo: Tempo,
+    looping: bool,
+    // time left in the current note or articulation gap
+    left_ms: u32,
+    // articulation gap still to come after the current note
+    gap_ms: u32,
+}
+
+impl<'a, O: ToneOutput> MelodyPlayer<'a, O> {
+    /// Create an idle player on a tone output.
+    pub fn new(output: O) -> Self {
+        Self {
+            output,
+            melody: None,
+            notes: None,
+            tempo: Tempo::DEFAULT,
+            looping: false,
+            left_ms: 0,
+            gap_ms: 0,
+        }
+    }
+
+    /// Start playing `melody` from its first note, replacing the melody
+    /// currently playing.
+    ///
+    /// The whole melody is checked first, so a melody that fails to parse is
+    /// not played at all.
+    pub fn play(&mut self, melody: Melody<'a>) -> Result<(), ParseError> {
+        melody.validate()?;
+        self.tempo = melody.tempo();
+        self.melody = Some(melody);
+        self.notes = Some(melody.notes());
+        if self.next_note().is_none() {
+            // an empty melody finishes immediately
+            self.stop();
+        }
+        Ok(())
+    }
+
+    /// Play the melody again from the start each time it finishes.
+    pub fn set_looping(&mut self, looping: bool) {
+        self.looping = looping;
+    }
+
+    /// Change the number of beats per minute of the current melody.
+    ///
+    /// This takes effect from the next note and lasts until another melody
+    /// is played.
+    pub fn set_bpm(&mut self, bpm: u16) {
+        self.tempo.bpm = bpm;
+    }
+
+    /// Returns the tempo the current melody is played at.
+    pub fn tempo(&self) -> Tempo {
+        self.tempo
+    }
+
+    /// Returns `true` while a melody is playing.
+    pub fn is_playing(&self) -> bool {
+        self.notes.is_some()
+    }
+
+    /// Stop the melody currently playing, if any.
+    pub fn stop(&mut self) {
+        self.output.stop_tone();
+        self.melody = None;
+        self.notes = None;
+    }
+
+    /// Advance the melody by `elapsed_ms` milliseconds, starting and
+    /// stopping tones as needed.
+    pub fn tick(&mut self, elapsed_ms: u32) -> Option<MelodyEvent> {
+        let mut elapsed_ms = elapsed_ms;
+        let mut event = None;
+        while self.notes.is_some() {
+            if elapsed_ms < self.left_ms {
+                self.left_ms -= elapsed_ms;
+                break;
+            }
+            elapsed_ms -= self.left_ms;
+
+            if self.gap_ms > 0 {
+                self.output.stop_tone();
+                self.left_ms = self.gap_ms;
+                self.gap_ms = 0;
+                continue;
+            }
+            if self.next_note().is_some() {
+                continue;
+            }
+
+            match self.melody {
+                Some(melody) if self.looping => {
+                    self.notes = Some(melody.notes());
+                    self.next_note();
+                    event = Some(MelodyEvent::Looped);
+                }
+                _ => {
+                    self.stop();
+                    event = Some(MelodyEvent::Finished);
+                }
+            }
+        }
+        event
+    }
+
+    /// Stop playing and give back the tone output.
+    pub fn free(mut self) -> O {
+        if self.is_playing() {
+            self.stop();
+        }
+        self.output
+    }
+
+    /// Start the next note of the melody, if there is one.
+    fn next_note(&mut self) -> Option<Note> {
+        let note = self.notes.as_mut()?.next()?.ok()?;
+        let duration_ms = self.tempo.duration_ms(note.ticks).max(1);
+        if note.is_rest() {
+            self.output.stop_tone();
+            self.left_ms = duration_ms;
+            self.gap_ms = 0;
+        } else {
+            self.output.start_tone(u32::from(note.frequency_hz));
+            self.gap_ms = if duration_ms > 2 * ARTICULATION_MS {
+                ARTICULATION_MS
+            } else {
+                0
+            };
+            self.left_ms = duration_ms - self.gap_ms;
+        }
+        Some(note)
+    }
+}
@@ -0,0 +1,158 @@
+//! Temperature of the board.
+//!
+//! [`Temperature`] reads the sensor built into the nRF processor, which
+//! measures the temperature of the chip itself: like MakeCode's
+//! `input.temperature()`, it reads a little above the room temperature, and
+//! can be calibrated against a thermometer.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # use microbit_common as microbit;
+//! use microbit::{
+//!     sensors::{Celsius, Temperature},
+//!     Board,
+//! };
+//!
+//! let board = Board::take().unwrap();
+//! let mut temperature = Temperature::new(board.TEMP);
+//!
+//! // the room is at 21.5°C
+//! temperature.calibrate(Celsius::from_centi(2150));
+//! // in whole degrees, like MakeCode
+//! let degrees = temperature.measure().degrees();
+//! ```
+
+use crate::{
+    hal::temp::Temp,
+    motion::{Accelerometer, Error},
+    pac,
+};
+use core::{fmt, ops};
+use embedded_hal::i2c::I2c;
+
+/// A temperature, in hundredths of a degree Celsius.
+///
+/// ```
+/// # use microbit_common as microbit;
+/// use microbit::sensors::Celsius;
+///
+/// let temperature = Celsius::from_centi(2475) + Celsius::from_degrees(-3);
+/// assert_eq!(temperature.centi(), 2175);
+/// assert_eq!(temperature.degrees(), 21);
+/// assert_eq!(format!("{}", temperature), "21.75");
+/// assert_eq!(format!("{}", Celsius::from_centi(-5)), "-0.05");
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Celsius(i32);
+
+impl Celsius {
+    /// Create a temperature of `centi` hundredths of a degree.
+    pub const fn from_centi(centi: i32) -> Self {
+        Celsius(centi)
+    }
+
+    /// Create a temperature of whole `degrees`.
+    pub const fn from_degrees(degrees: i32) -> Self {
+        Celsius(degrees * 100)
+    }
+
+    /// Returns the temperature in hundredths of a degree.
+    pub const fn centi(self) -> i32 {
+        self.0
+    }
+
+    /// Returns the temperature in whole degrees, rounded towards zero like
+    /// MakeCode.
+    pub const fn degrees(self) -> i32 {
+        self.0 / 100
+    }
+}
+
+impl ops::Add for Celsius {
+    type Output = Celsius;
+
+    fn add(self, other: Celsius) -> Celsius {
+        Celsius(self.0 + other.0)
+    }
+}
+
+impl ops::Sub for Celsius {
+    type Output = Celsius;
+
+    fn sub(self, other: Celsius) -> Celsius {
+        Celsius(self.0 - other.0)
+    }
+}
+
+impl fmt::Display for Celsius {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let centi = self.0.unsigned_abs();
+        write!(f, "{}{}.{:02}", sign, centi / 100, centi % 100)
+    }
+}
+
+/// The temperature sensor of the nRF processor, with a calibration offset.
+///
+/// The sensor has a resolution of a quarter of a degree. The offset, zero
+/// to start with, is added to every measure, including the ones of the
+/// motion sensor with [`measure_motion_sensor`](Self::measure_motion_sensor).
+pub struct Temperature {
+    temp: Temp,
+    offset: Celsius,
+}
+
+impl Temperature {
+    /// Create the sensor from the `TEMP` peripheral.
+    pub fn new(temp: pac::TEMP) -> Self {
+        Self {
+            temp: Temp::new(temp),
+            offset: Celsius::default(),
+        }
+    }
+
+    /// Returns the offset added to the measures.
+    pub fn offset(&self) -> Celsius {
+        self.offset
+    }
+
+    /// Set the offset added to the measures.
+    pub fn set_offset(&mut self, offset: Celsius) {
+        self.offset = offset;
+    }
+
+    /// Set the offset so the current measure is `actual`, the temperature a
+    /// thermometer reads, like MakeCode's `input.setTemperatureCalibration`.
+    pub fn calibrate(&mut self, actual: Celsius) {
+        self.offset = actual - self.raw();
+    }
+
+    /// Measure the temperature, blocking for a few tens of microseconds.
+    pub fn measure(&mut self) -> Celsius {
+        self.raw() + self.offset
+    }
+
+    /// Measure the temperature with the motion sensor instead, when it has
+    /// a temperature sensor, see [`Accelerometer::temperature`].
+    ///
+    /// The motion sensor may read closer to the room temperature, being
+    /// further from the processor. The offset applies to these measures
+    /// too, but [`calibrate`](Self::calibrate) works it out for the
+    /// processor: use [`set_offset`](Self::set_offset) to calibrate the
+    /// motion sensor.
+    pub fn measure_motion_sensor<I2C: I2c>(
+        &self,
+        accelerometer: &mut Accelerometer<I2C>,
+    ) -> Result<Option<Celsius>, Error<I2C::Error>> {
+        Ok(accelerometer
+            .temperature()?
+            .map(|temperature| temperature + self.offset))
+    }
+
+    /// Returns the temperature of the processor, without the offset.
+    fn raw(&mut self) -> Celsius {
+        // in quarters of a degree
+        Celsius(self.temp.measure().to_bits() * 25)
+    }
+}
@@ -0,0 +1,213 @@
+//! Tones on the micro:bit V2 on-board speaker.
+//!
+//! The speaker is connected to `P0_00` and driven with a square wave from one
+//! of the PWM peripherals. [`Speaker`] configures the PWM and plays tones of a
+//! given frequency and duration without blocking: the PWM stops by itself at
+//! the end of the tone, so no interrupt or polling is needed.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # use microbit_common as microbit;
+//! use embedded_hal::delay::DelayNs;
+//! use microbit::{hal::Timer, speaker::Speaker, Board};
+//!
+//! let board = Board::take().unwrap();
+//! let mut timer = Timer::new(board.TIMER0);
+//! let mut speaker = Speaker::new(board.PWM0, board.speaker_pin);
+//!
+//! speaker.set_volume(128);
+//! // play an A4 for half a second, this returns immediately
+//! speaker.play_tone(440, 500);
+//! timer.delay_ms(1000);
+//! ```
+//!
+//! # Technical details
+//!
+//! The PWM runs in up counter mode from a 500kHz clock, so tones from 16Hz to
+//! 20kHz can be played. The volume sets the duty cycle, from silent to a 50%
+//! square wave at the maximum volume.
+//!
+//! A tone is played as a one value sequence with a refresh count matching its
+//! duration, followed by a one value sequence of silence. The `LOOPSDONE_STOP`
+//! shortcut then stops the PWM.
+
+use crate::{
+    hal::{
+        gpio::{p0::P0_00, Disconnected, Level, Output, Pin, PushPull},
+        pwm::Instance,
+    },
+    music::ToneOutput,
+};
+use core::ptr::{addr_of, addr_of_mut};
+
+/// Frequency of the PWM clock, in Hz.
+const PWM_CLOCK_HZ: u32 = 500_000;
+
+/// Lowest tone frequency that fits in the 15 bit counter, in Hz.
+pub const MIN_FREQUENCY_HZ: u32 = 16;
+
+/// Highest tone frequency, in Hz.
+pub const MAX_FREQUENCY_HZ: u32 = 20_000;
+
+/// Default volume of a new [`Speaker`].
+pub const DEFAULT_VOLUME: u8 = 255;
+
+/// Sets the polarity of a sequence value so the output is high for the
+/// compare value and low for the rest of the period.
+const FALLING_EDGE: u16 = 0x8000;
+
+/// Sequence value keeping the output low for the whole period.
+const SILENT: u16 = FALLING_EDGE;
+
+/// Largest refresh count, the REFRESH register is 24 bits wide.
+const MAX_REFRESH: u32 = 0x00FF_FFFF;
+
+// EasyDMA reads the sequences from RAM while a tone plays, so they need a
+// fixed address. There is only one speaker pin, so there is only ever one
+// `Speaker` using them.
+static mut TONE: [u16; 1] = [SILENT];
+static mut SILENCE: [u16; 1] = [SILENT];
+
+/// The micro:bit V2 on-board speaker.
+///
+/// Create one with [`Speaker::new`] from the speaker pin and any PWM
+/// peripheral, see the [module documentation](self) for an example.
+pub struct Speaker<T: Instance> {
+    pwm: T,
+    pin: Pin<Output<PushPull>>,
+    volume: u8,
+}
+
+impl<T: Instance> Speaker<T> {
+    /// Create a [`Speaker`] from a PWM peripheral and the speaker pin,
+    /// exposed by [`Board`](crate::board::Board) as `speaker_pin`.
+    pub fn new(pwm: T, pin: P0_00<Disconnected>) -> Self {
+        let pin = pin.into_push_pull_output(Level::Low).degrade();
+
+        pwm.psel.out[0].write(|w| unsafe { w.bits(pin.psel_bits()) });
+        for out in &pwm.psel.out[1..] {
+            out.write(|w| w.connect().disconnected());
+        }
+        pwm.mode.write(|w| w.updown().up());
+        pwm.prescaler.write(|w| w.prescaler().div_32());
+        pwm.decoder
+            .write(|w| w.load().common().mode().refresh_count());
+        pwm.seq0.enddelay.write(|w| unsafe { w.bits(0) });
+        pwm.seq1.enddelay.write(|w| unsafe { w.bits(0) });
+        pwm.enable.write(|w| w.enable().enabled());
+
+        Self {
+            pwm,
+            pin,
+            volume: DEFAULT_VOLUME,
+        }
+    }
+
+    /// Set the volume of the following tones, from `0` (silent) to `255`.
+    pub fn set_volume(&mut self, volume: u8) {
+        self.volume = volume;
+    }
+
+    /// Returns the current volume.
+    pub fn volume(&self) -> u8 {
+        self.volume
+    }
+
+    /// Start playing a tone of `freq_hz` for `duration_ms` milliseconds.
+    ///
+    /// This returns immediately; the tone stops by itself once the duration
+    /// has elapsed. Playing a tone replaces the one currently playing.
+    ///
+    /// The frequency is clamped to [`MIN_FREQUENCY_HZ`]..=[`MAX_FREQUENCY_HZ`].
+    pub fn play_tone(&mut self, freq_hz: u32, duration_ms: u32) {
+        let top = self.start(freq_hz);
+        let periods = duration_ms.saturating_mul(PWM_CLOCK_HZ / 1000) / top;
+
+        let pwm = &self.pwm;
+        pwm.seq0
+            .refresh
+            .write(|w| unsafe { w.bits(periods.clamp(1, MAX_REFRESH + 1) - 1) });
+        pwm.seq1.refresh.write(|w| unsafe { w.bits(0) });
+        // play the tone, then the silence, then stop
+        pwm.loop_.write(|w| unsafe { w.cnt().bits(1) });
+        pwm.shorts.write(|w| w.loopsdone_stop().enabled());
+        pwm.tasks_seqstart[0].write(|w| unsafe { w.bits(1) });
+    }
+
+    /// Start playing a tone of `freq_hz` until [`Speaker::stop`] is called or
+    /// another tone is played.
+    ///
+    /// The frequency is clamped to [`MIN_FREQUENCY_HZ`]..=[`MAX_FREQUENCY_HZ`].
+    pub fn start_tone(&mut self, freq_hz: u32) {
+        self.start(freq_hz);
+
+        let pwm = &self.pwm;
+        pwm.seq0.refresh.write(|w| unsafe { w.bits(0) });
+        // the last value of a sequence is kept once it has been played
+        pwm.loop_.write(|w| w.cnt().disabled());
+        pwm.shorts.reset();
+        pwm.tasks_seqstart[0].write(|w| unsafe { w.bits(1) });
+    }
+
+    /// Stop the tone currently playing, if any.
+    pub fn stop(&mut self) {
+        self.pwm.tasks_stop.write(|w| unsafe { w.bits(1) });
+    }
+
+    /// Returns `true` while a tone is playing.
+    pub fn is_playing(&self) -> bool {
+        self.pwm.events_seqstarted[0].read().bits() != 0
+            && self.pwm.events_stopped.read().bits() == 0
+    }
+
+    /// Stop the PWM and give back the peripheral and the speaker pin.
+    pub fn free(self) -> (T, Pin<Output<PushPull>>) {
+        self.pwm.tasks_stop.write(|w| unsafe { w.bits(1) });
+        self.pwm.enable.write(|w| w.enable().disabled());
+        self.pwm.psel.out[0].write(|w| w.connect().disconnected());
+        (self.pwm, self.pin)
+    }
+
+    /// Stop any playing tone, load a tone of `freq_hz` at the current volume
+    /// into the sequences and return the counter top used for it.
+    fn start(&mut self, freq_hz: u32) -> u32 {
+        self.pwm.tasks_stop.write(|w| unsafe { w.bits(1) });
+        while self.pwm.events_seqstarted[0].read().bits() != 0
+            && self.pwm.events_stopped.read().bits() == 0
+        {}
+        self.pwm.events_seqstarted[0].reset();
+        self.pwm.events_stopped.reset();
+
+        let freq_hz = freq_hz.clamp(MIN_FREQUENCY_HZ, MAX_FREQUENCY_HZ);
+        let top = PWM_CLOCK_HZ / freq_hz;
+        // a 50% duty cycle is the loudest a square wave gets
+        let duty = top / 2 * u32::from(self.volume) / 255;
+        unsafe {
+            *addr_of_mut!(TONE) = [FALLING_EDGE | duty as u16];
+        }
+
+        let pwm = &self.pwm;
+        pwm.countertop
+            .write(|w| unsafe { w.countertop().bits(top as u16) });
+        pwm.seq0
+            .ptr
+            .write(|w| unsafe { w.bits(addr_of!(TONE) as u32) });
+        pwm.seq0.cnt.write(|w| unsafe { w.bits(1) });
+        pwm.seq1
+            .ptr
+            .write(|w| unsafe { w.bits(addr_of!(SILENCE) as u32) });
+        pwm.seq1.cnt.write(|w| unsafe { w.bits(1) });
+        top
+    }
+}
+
+impl<T: Instance> ToneOutput for Speaker<T> {
+    fn start_tone(&mut self, freq_hz: u32) {
+        Speaker::start_tone(self, freq_hz);
+    }
+
+    fn stop_tone(&mut self) {
+        self.stop();
+    }
+}
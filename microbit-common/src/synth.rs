@@ -0,0 +1,828 @@
+//! Sound effects synthesizer, compatible with MakeCode sound expressions.
+//!
+//! The micro:bit V2 firmware plays sound effects built from segments: each
+//! segment sweeps a waveform from a start to an end frequency and volume,
+//! optionally with a vibrato, tremolo or warble effect. This module renders
+//! such segments into signed 16-bit PCM samples, ready for the V2
+//! `audio::PcmPlayer`.
+//!
+//! Segments can be written in code or parsed from the 72 digit strings
+//! MakeCode stores in its programs, see [`Segment::parse`]. The built-in
+//! [`SoundExpression`]s cover the sounds of the V2 firmware.
+//!
+//! # Example
+//!
+//! ```
+//! # use microbit_common as microbit;
+//! use microbit::synth::{Segment, Waveform};
+//!
+//! // a 1kHz square wave for 10ms
+//! let segment = Segment::new(Waveform::Square, 1000, 1000, 1023, 1023, 10);
+//! let samples: Vec<i16> = segment.samples(8_000).collect();
+//! assert_eq!(samples.len(), 80);
+//!
+//! // 10 periods of a 1kHz wave, so 20 sign changes
+//! let sign_changes = samples
+//!     .windows(2)
+//!     .filter(|pair| (pair[0] < 0) != (pair[1] < 0))
+//!     .count();
+//! assert!((19..=20).contains(&sign_changes));
+//! ```
+//!
+//! Only `core` is needed, so the synthesizer runs and can be tested on the
+//! host.
+
+use core::{fmt, str};
+
+/// Number of characters of a MakeCode sound expression segment.
+pub const SEGMENT_LEN: usize = 72;
+
+/// Largest volume of a segment.
+pub const MAX_VOLUME: u16 = 1023;
+
+/// Seed of the noise and randomness generator, unless another one is set.
+const DEFAULT_SEED: u32 = 0x1234_5678;
+
+/// The waveform of a [`Segment`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Waveform {
+    /// A sine wave.
+    Sine,
+    /// A rising sawtooth wave.
+    Sawtooth,
+    /// A triangle wave.
+    Triangle,
+    /// A square wave.
+    Square,
+    /// White noise, sampled once per half period.
+    Noise,
+}
+
+/// How the frequency and volume of a [`Segment`] move from their start to
+/// their end values.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Interpolation {
+    /// Stay at the start values.
+    None,
+    /// Move at a constant rate.
+    Linear,
+    /// Start and end slowly, move fastest in the middle.
+    Curve,
+    /// Start slowly and speed up.
+    ExponentialRising,
+    /// Start fast and slow down.
+    ExponentialFalling,
+    /// Start fast and slow down, more sharply than `ExponentialFalling`.
+    Logarithmic,
+    /// Any other MakeCode shape code, such as the arpeggios. Rendered as
+    /// `Linear`.
+    Other(u8),
+}
+
+impl Interpolation {
+    fn from_code(code: u8) -> Self {
+        match code {
+            0 => Interpolation::None,
+            1 => Interpolation::Linear,
+            2 => Interpolation::Curve,
+            5 => Interpolation::ExponentialRising,
+            6 => Interpolation::ExponentialFalling,
+            18 => Interpolation::Logarithmic,
+            code => Interpolation::Other(code),
+        }
+    }
+
+    fn code(&self) -> u8 {
+        match self {
+            Interpolation::None => 0,
+            Interpolation::Linear => 1,
+            Interpolation::Curve => 2,
+            Interpolation::ExponentialRising => 5,
+            Interpolation::ExponentialFalling => 6,
+            Interpolation::Logarithmic => 18,
+            Interpolation::Other(code) => *code,
+        }
+    }
+
+    /// Shape a progress from `0` to `1 << 16`.
+    fn apply(&self, progress: u32) -> u32 {
+        const ONE: u64 = 1 << 16;
+        let p = u64::from(progress.min(1 << 16));
+        let shaped = match self {
+            Interpolation::None => 0,
+            Interpolation::Linear | Interpolation::Other(_) => p,
+            Interpolation::Curve => {
+                // smoothstep: 3p² - 2p³
+                let p2 = (p * p) >> 16;
+                let p3 = (p2 * p) >> 16;
+                3 * p2 - 2 * p3
+            }
+            Interpolation::ExponentialRising => (p * p) >> 16,
+            Interpolation::ExponentialFalling => ONE - (((ONE - p) * (ONE - p)) >> 16),
+            Interpolation::Logarithmic => ONE - (((ONE - p) * (ONE - p) * (ONE - p)) >> 32),
+        };
+        shaped as u32
+    }
+}
+
+/// An effect applied over a whole [`Segment`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Effect {
+    /// No effect.
+    None,
+    /// The frequency alternates `fx_param` Hz above and below its value.
+    Vibrato,
+    /// The volume alternates `fx_param` above and below its value.
+    Tremolo,
+    /// The frequency jumps randomly within `fx_param` Hz of its value.
+    Warble,
+}
+
+/// Random variations applied each time a [`Segment`] is rendered, as
+/// encoded in MakeCode sound expressions.
+///
+/// Each value is the largest variation, up or down, of the matching
+/// segment parameter.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Randomness {
+    /// Variation of the start frequency, in Hz.
+    pub frequency: u16,
+    /// Variation of the end frequency, in Hz.
+    pub end_frequency: u16,
+    /// Variation of the start volume.
+    pub volume: u16,
+    /// Variation of the end volume.
+    pub end_volume: u16,
+    /// Variation of the duration, in milliseconds.
+    pub duration: u16,
+    /// Variation of the effect parameter.
+    pub fx_param: u16,
+    /// Variation of the number of effect steps.
+    pub fx_steps: u16,
+}
+
+/// An error found while parsing a MakeCode sound expression.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ParseError {
+    /// The segment at this index is not [`SEGMENT_LEN`] characters long.
+    Length(usize),
+    /// The field starting at this byte offset is not a number or is out of
+    /// range.
+    Field(usize),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::Length(index) => write!(f, "segment {} has the wrong length", index),
+            ParseError::Field(offset) => write!(f, "invalid field at offset {}", offset),
+        }
+    }
+}
+
+/// One part of a sound effect.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Segment {
+    /// The waveform played.
+    pub waveform: Waveform,
+    /// Frequency at the start, in Hz.
+    pub start_frequency_hz: u16,
+    /// Frequency at the end, in Hz.
+    pub end_frequency_hz: u16,
+    /// Volume at the start, from `0` to [`MAX_VOLUME`].
+    pub start_volume: u16,
+    /// Volume at the end, from `0` to [`MAX_VOLUME`].
+    pub end_volume: u16,
+    /// Duration, in milliseconds.
+    pub duration_ms: u16,
+    /// How frequency and volume move from their start to their end values.
+    pub shape: Interpolation,
+    /// Number of steps the frequency and volume change in, `0` for a smooth
+    /// change.
+    pub steps: u16,
+    /// The effect applied.
+    pub fx: Effect,
+    /// Strength of the effect.
+    pub fx_param: u16,
+    /// Number of times the effect alternates over the segment.
+    pub fx_steps: u16,
+    /// Random variations applied each time the segment is rendered.
+    pub randomness: Randomness,
+}
+
+impl Segment {
+    /// Create a segment moving linearly between two frequencies and volumes,
+    /// without effect.
+    pub const fn new(
+        waveform: Waveform,
+        start_frequency_hz: u16,
+        end_frequency_hz: u16,
+        start_volume: u16,
+        end_volume: u16,
+        duration_ms: u16,
+    ) -> Self {
+        Self {
+            waveform,
+            start_frequency_hz,
+            end_frequency_hz,
+            start_volume,
+            end_volume,
+            duration_ms,
+            shape: Interpolation::Linear,
+            steps: 0,
+            fx: Effect::None,
+            fx_param: 0,
+            fx_steps: 0,
+            randomness: Randomness {
+                frequency: 0,
+                end_frequency: 0,
+                volume: 0,
+                end_volume: 0,
+                duration: 0,
+                fx_param: 0,
+                fx_steps: 0,
+            },
+        }
+    }
+
+    /// Returns this segment with a different interpolation shape.
+    pub const fn with_shape(self, shape: Interpolation) -> Self {
+        Self { shape, ..self }
+    }
+
+    /// Returns this segment changing frequency and volume in `steps` steps.
+    pub const fn with_steps(self, steps: u16) -> Self {
+        Self { steps, ..self }
+    }
+
+    /// Returns this segment with an effect.
+    pub const fn with_effect(self, fx: Effect, fx_param: u16, fx_steps: u16) -> Self {
+        Self {
+            fx,
+            fx_param,
+            fx_steps,
+            ..self
+        }
+    }
+
+    /// Parse a segment from the 72 digit format of MakeCode sound
+    /// expressions.
+    ///
+    /// ```
+    /// # use microbit_common as microbit;
+    /// use microbit::synth::{Effect, Segment, Waveform};
+    ///
+    /// let makecode =
+    ///     "010230988019008000044000001023001601003300240000000000000000000000000000";
+    /// let segment = Segment::parse(makecode).unwrap();
+    /// assert_eq!(segment.waveform, Waveform::Sine);
+    /// assert_eq!(segment.start_frequency_hz, 988);
+    /// assert_eq!(segment.end_frequency_hz, 440);
+    /// assert_eq!(segment.duration_ms, 190);
+    /// assert_eq!(segment.fx, Effect::Vibrato);
+    ///
+    /// // and back again
+    /// assert_eq!(segment.encode(), makecode.as_bytes());
+    /// ```
+    pub fn parse(makecode: &str) -> Result<Self, ParseError> {
+        Self::parse_at(makecode, 0, 0)
+    }
+
+    /// Parse the segment at `index`, starting at byte `offset` of a sound
+    /// expression.
+    fn parse_at(makecode: &str, index: usize, offset: usize) -> Result<Self, ParseError> {
+        let bytes = makecode.as_bytes();
+        if bytes.len() != SEGMENT_LEN {
+            return Err(ParseError::Length(index));
+        }
+        let field = |start: usize, len: usize| -> Result<u16, ParseError> {
+            bytes[start..start + len]
+                .iter()
+                .try_fold(0u16, |acc, &digit| {
+                    if digit.is_ascii_digit() {
+                        Some(acc * 10 + u16::from(digit - b'0'))
+                    } else {
+                        None
+                    }
+                })
+                .ok_or(ParseError::Field(offset + start))
+        };
+        let volume = |start: usize| {
+            field(start, 4).and_then(|volume| {
+                if volume <= MAX_VOLUME {
+                    Ok(volume)
+                } else {
+                    Err(ParseError::Field(offset + start))
+                }
+            })
+        };
+
+        let waveform = match field(0, 1)? {
+            0 => Waveform::Sine,
+            1 => Waveform::Sawtooth,
+            2 => Waveform::Triangle,
+            3 => Waveform::Square,
+            4 => Waveform::Noise,
+            _ => return Err(ParseError::Field(offset)),
+        };
+        let fx = match field(34, 2)? {
+            0 => Effect::None,
+            1 => Effect::Vibrato,
+            2 => Effect::Tremolo,
+            3 => Effect::Warble,
+            _ => return Err(ParseError::Field(offset + 34)),
+        };
+        Ok(Self {
+            waveform,
+            start_volume: volume(1)?,
+            start_frequency_hz: field(5, 4)?,
+            duration_ms: field(9, 4)?,
+            shape: Interpolation::from_code(field(13, 2)? as u8),
+            // 15..18 and 22..26 are legacy fields, ignored by the firmware
+            end_frequency_hz: field(18, 4)?,
+            end_volume: volume(26)?,
+            steps: field(30, 4)?,
+            fx,
+            fx_param: field(36, 4)?,
+            fx_steps: field(40, 4)?,
+            randomness: Randomness {
+                frequency: field(44, 4)?,
+                end_frequency: field(48, 4)?,
+                volume: field(52, 4)?,
+                end_volume: field(56, 4)?,
+                duration: field(60, 4)?,
+                fx_param: field(64, 4)?,
+                fx_steps: field(68, 4)?,
+            },
+        })
+    }
+
+    /// Encode this segment in the 72 digit format of MakeCode sound
+    /// expressions.
+    ///
+    /// Values too large for their field are clamped, and the legacy fields
+    /// are encoded as zeros.
+    pub fn encode(&self) -> [u8; SEGMENT_LEN] {
+        let mut out = [b'0'; SEGMENT_LEN];
+        let mut put = |start: usize, len: usize, value: u16| {
+            let max = 10u32.pow(len as u32) - 1;
+            let mut value = u32::from(value).min(max);
+            for digit in out[start..start + len].iter_mut().rev() {
+                *digit = b'0' + (value % 10) as u8;
+                value /= 10;
+            }
+        };
+        let waveform = match self.waveform {
+            Waveform::Sine => 0,
+            Waveform::Sawtooth => 1,
+            Waveform::Triangle => 2,
+            Waveform::Square => 3,
+            Waveform::Noise => 4,
+        };
+        let fx = match self.fx {
+            Effect::None => 0,
+            Effect::Vibrato => 1,
+            Effect::Tremolo => 2,
+            Effect::Warble => 3,
+        };
+        put(0, 1, waveform);
+        put(1, 4, self.start_volume.min(MAX_VOLUME));
+        put(5, 4, self.start_frequency_hz);
+        put(9, 4, self.duration_ms);
+        put(13, 2, self.shape.code().into());
+        put(18, 4, self.end_frequency_hz);
+        put(26, 4, self.end_volume.min(MAX_VOLUME));
+        put(30, 4, self.steps);
+        put(34, 2, fx);
+        put(36, 4, self.fx_param);
+        put(40, 4, self.fx_steps);
+        let randomness = &self.randomness;
+        put(44, 4, randomness.frequency);
+        put(48, 4, randomness.end_frequency);
+        put(52, 4, randomness.volume);
+        put(56, 4, randomness.end_volume);
+        put(60, 4, randomness.duration);
+        put(64, 4, randomness.fx_param);
+        put(68, 4, randomness.fx_steps);
+        out
+    }
+
+    /// Render this segment at `sample_rate_hz`.
+    pub fn samples(&self, sample_rate_hz: u32) -> SegmentSamples {
+        SegmentSamples::new(*self, sample_rate_hz, &mut Rng(DEFAULT_SEED))
+    }
+}
+
+/// Pseudo-random numbers for noise, warble and randomness (xorshift32).
+#[derive(Copy, Clone, Debug)]
+struct Rng(u32);
+
+impl Rng {
+    fn next(&mut self) -> u32 {
+        let mut x = self.0;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.0 = x;
+        x
+    }
+
+    /// A random value in `-range..=range`.
+    fn vary(&mut self, range: u16) -> i32 {
+        if range == 0 {
+            return 0;
+        }
+        let span = 2 * u32::from(range) + 1;
+        (self.next() % span) as i32 - i32::from(range)
+    }
+}
+
+/// Apply a random variation to `value`, keeping it within `0..=max`.
+fn vary(rng: &mut Rng, value: u16, range: u16, max: u16) -> u16 {
+    (i32::from(value) + rng.vary(range)).clamp(0, i32::from(max)) as u16
+}
+
+/// The samples of one [`Segment`], returned by [`Segment::samples`].
+#[derive(Clone, Debug)]
+pub struct SegmentSamples {
+    segment: Segment,
+    sample_rate_hz: u32,
+    index: u32,
+    len: u32,
+    // phase of the waveform, a full period being `1 << 32`
+    phase: u32,
+    rng: Rng,
+    noise: i16,
+    warble_step: u32,
+    warble_offset: i32,
+}
+
+impl SegmentSamples {
+    fn new(segment: Segment, sample_rate_hz: u32, rng: &mut Rng) -> Self {
+        // pick this rendering's random variations
+        let r = segment.randomness;
+        let mut segment = segment;
+        segment.start_frequency_hz = vary(rng, segment.start_frequency_hz, r.frequency, u16::MAX);
+        segment.end_frequency_hz = vary(rng, segment.end_frequency_hz, r.end_frequency, u16::MAX);
+        segment.start_volume = vary(rng, segment.start_volume, r.volume, MAX_VOLUME);
+        segment.end_volume = vary(rng, segment.end_volume, r.end_volume, MAX_VOLUME);
+        segment.duration_ms = vary(rng, segment.duration_ms, r.duration, u16::MAX);
+        segment.fx_param = vary(rng, segment.fx_param, r.fx_param, u16::MAX);
+        segment.fx_steps = vary(rng, segment.fx_steps, r.fx_steps, u16::MAX);
+
+        let len = u32::from(segment.duration_ms) * sample_rate_hz / 1000;
+        Self {
+            segment,
+            sample_rate_hz,
+            index: 0,
+            len,
+            phase: 0,
+            rng: Rng(rng.next() | 1),
+            noise: 0,
+            warble_step: u32::MAX,
+            warble_offset: 0,
+        }
+    }
+
+    /// Progress through the segment from `0` to `1 << 16`, in steps if the
+    /// segment has any.
+    fn progress(&self) -> u32 {
+        let index = u64::from(self.index);
+        let len = u64::from(self.len.max(1));
+        let steps = u64::from(self.segment.steps);
+        let progress = match (index * steps / len * (1 << 16)).checked_div(steps) {
+            Some(progress) => progress,
+            None => index * (1 << 16) / len,
+        };
+        progress.min(1 << 16) as u32
+    }
+
+    /// Returns `+1` or `-1`, alternating `fx_steps` times over the segment.
+    fn fx_sign(&self) -> i32 {
+        if self.fx_step() & 1 == 0 {
+            1
+        } else {
+            -1
+        }
+    }
+
+    fn fx_step(&self) -> u32 {
+        let len = u64::from(self.len.max(1));
+        (u64::from(self.index) * u64::from(self.segment.fx_steps) / len) as u32
+    }
+
+    fn wave(&mut self, phase: u32) -> i32 {
+        let half = phase >> 16;
+        match self.segment.waveform {
+            Waveform::Sine => sine(phase),
+            Waveform::Sawtooth => half as i32 - 32_768,
+            Waveform::Triangle => {
+                if half < 32_768 {
+                    2 * half as i32 - 32_767
+                } else {
+                    32_767 - 2 * (half as i32 - 32_768)
+                }
+            }
+            Waveform::Square => {
+                if half < 32_768 {
+                    32_767
+                } else {
+                    -32_767
+                }
+            }
+            Waveform::Noise => i32::from(self.noise),
+        }
+    }
+}
+
+impl Iterator for SegmentSamples {
+    type Item = i16;
+
+    fn next(&mut self) -> Option<i16> {
+        if self.index >= self.len {
+            return None;
+        }
+        let segment = &self.segment;
+        let shaped = i64::from(segment.shape.apply(self.progress()));
+        let lerp = |start: u16, end: u16| {
+            i64::from(start) + (((i64::from(end) - i64::from(start)) * shaped) >> 16)
+        };
+        let mut frequency = lerp(segment.start_frequency_hz, segment.end_frequency_hz) as i32;
+        let mut volume = lerp(segment.start_volume, segment.end_volume) as i32;
+
+        match segment.fx {
+            Effect::None => {}
+            Effect::Vibrato => frequency += self.fx_sign() * i32::from(segment.fx_param),
+            Effect::Tremolo => volume += self.fx_sign() * i32::from(segment.fx_param),
+            Effect::Warble => {
+                let step = self.fx_step();
+                if step != self.warble_step {
+                    self.warble_step = step;
+                    self.warble_offset = self.rng.vary(segment.fx_param);
+                }
+                frequency += self.warble_offset;
+            }
+        }
+        let frequency = frequency.max(0) as u64;
+        let volume = volume.clamp(0, i32::from(MAX_VOLUME));
+
+        let value = self.wave(self.phase);
+        let step = (frequency << 32) / u64::from(self.sample_rate_hz.max(1));
+        let next_phase = self.phase.wrapping_add(step as u32);
+        if (next_phase ^ self.phase) & 0x8000_0000 != 0 || step >= 1 << 31 {
+            // new half period: new noise value
+            self.noise = self.rng.next() as i16;
+        }
+        self.phase = next_phase;
+        self.index += 1;
+        Some((value * volume / i32::from(MAX_VOLUME)) as i16)
+    }
+}
+
+/// Sine of a phase where a full period is `1 << 32`, from `-32767` to
+/// `32767`, using Bhaskara I's approximation.
+pub(crate) fn sine(phase: u32) -> i32 {
+    // position within the half period, from 0 to 1 << 16
+    let t = i64::from((phase & 0x7FFF_FFFF) >> 15);
+    let u = (t * ((1 << 16) - t)) >> 16;
+    let value = (16 * u * 32_767 / (5 * (1 << 16) - 4 * u)) as i32;
+    if phase & 0x8000_0000 == 0 {
+        value
+    } else {
+        -value
+    }
+}
+
+/// A sound effect made of several segments, parsed from a MakeCode sound
+/// expression: segments in the 72 digit format, separated by commas.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Sound<'a> {
+    makecode: &'a str,
+}
+
+impl<'a> Sound<'a> {
+    /// Parse a MakeCode sound expression.
+    ///
+    /// All segments are checked, so iterating over them can't fail.
+    pub fn parse(makecode: &'a str) -> Result<Self, ParseError> {
+        let mut offset = 0;
+        for (index, segment) in makecode.split(',').enumerate() {
+            Segment::parse_at(segment, index, offset)?;
+            offset += segment.len() + 1;
+        }
+        Ok(Self { makecode })
+    }
+
+    /// Returns an iterator over the segments of this sound.
+    pub fn segments(&self) -> impl Iterator<Item = Segment> + 'a {
+        self.makecode
+            .split(',')
+            .filter_map(|segment| Segment::parse(segment).ok())
+    }
+
+    /// Render this sound at `sample_rate_hz`.
+    pub fn samples(&self, sample_rate_hz: u32) -> Samples<impl Iterator<Item = Segment> + 'a> {
+        Samples::new(self.segments(), sample_rate_hz)
+    }
+}
+
+/// The samples of several segments, one after the other.
+///
+/// Returned by [`Sound::samples`] and [`SoundExpression::samples`], or
+/// created from any iterator of segments.
+#[derive(Clone, Debug)]
+pub struct Samples<I> {
+    segments: I,
+    current: Option<SegmentSamples>,
+    sample_rate_hz: u32,
+    rng: Rng,
+}
+
+impl<I: Iterator<Item = Segment>> Samples<I> {
+    /// Render `segments` at `sample_rate_hz`.
+    pub fn new(segments: I, sample_rate_hz: u32) -> Self {
+        Self::with_seed(segments, sample_rate_hz, DEFAULT_SEED)
+    }
+
+    /// Render `segments` at `sample_rate_hz`, seeding the noise and
+    /// [`Randomness`] with `seed`.
+    pub fn with_seed(segments: I, sample_rate_hz: u32, seed: u32) -> Self {
+        Self {
+            segments,
+            current: None,
+            sample_rate_hz,
+            rng: Rng(seed | 1),
+        }
+    }
+}
+
+impl<I: Iterator<Item = Segment>> Iterator for Samples<I> {
+    type Item = i16;
+
+    fn next(&mut self) -> Option<i16> {
+        loop {
+            if let Some(sample) = self.current.as_mut().and_then(Iterator::next) {
+                return Some(sample);
+            }
+            let segment = self.segments.next()?;
+            self.current = Some(SegmentSamples::new(
+                segment,
+                self.sample_rate_hz,
+                &mut self.rng,
+            ));
+        }
+    }
+}
+
+/// The built-in sound effects of the micro:bit V2.
+///
+/// Each sound is the MakeCode sound expression of the CODAL sound of the
+/// same name, see [`makecode`](Self::makecode).
+///
+/// ```
+/// # use microbit_common as microbit;
+/// use microbit::synth::SoundExpression;
+///
+/// // render the start of a giggle into a buffer
+/// let mut buffer = [0i16; 512];
+/// for (sample, value) in buffer.iter_mut().zip(SoundExpression::Giggle.samples(8_000)) {
+///     *sample = value;
+/// }
+/// assert!(buffer.iter().any(|&sample| sample != 0));
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SoundExpression {
+    /// A short run of laughing chirps.
+    Giggle,
+    /// Two cheerful rising notes.
+    Happy,
+    /// A friendly rising greeting.
+    Hello,
+    /// A slow, wavering fall.
+    Mysterious,
+    /// Two falling, trembling notes.
+    Sad,
+    /// A smooth slide up.
+    Slide,
+    /// A long sweep up to high notes.
+    Soaring,
+    /// A bouncy, warbling rise.
+    Spring,
+    /// Stepping high notes.
+    Twinkle,
+    /// A long, sleepy fall.
+    Yawn,
+}
+
+const GIGGLE: &str = "010230988019008440044008881023001601003300240000000000000000000000000000,\
+    110232570087411440044008880352005901003300010000000000000000010000000000,\
+    310232729021105440288908880091006300000000240700020000000000003000000000,\
+    310232729010205440288908880091006300000000240700020000000000003000000000,\
+    310232729011405440288908880091006300000000240700020000000000003000000000";
+
+const HAPPY: &str = "010231992066911440044008880262002800001800020500000000000000010000000000,\
+    002322129029508440240408880000000400022400110000000000000000007500000000,\
+    000002129029509440240408880145000400022400110000000000000000007500000000";
+
+const HELLO: &str = "310230673019702440118708881023012800000000240000000000000000000000000000,\
+    300001064001602440098108880000012800000100040000000000000000000000000000,\
+    310231064029302440098108881023012800000100040000000000000000000000000000";
+
+const MYSTERIOUS: &str =
+    "400002390033100440240408880477000400022400110400000000000000008000000000,\
+    405512845385000440044008880000012803010500160000000000000000085000500015";
+
+const SAD: &str = "310231135036501440162408881023012800000100240000000000000000000000000000,\
+    310231237051302440183908881023012800000100240000000000000000000000000000";
+
+const SLIDE: &str = "105202325022302440115708880417012800000100240000000000000000000000000000,\
+    010232209079102440099908881023012800000100240000000000000000000000000000";
+
+const SOARING: &str = "210234000052102440106008880000010000000000240000000000000000000000000000,\
+    000001840052101440106008881023012800000000240000000000000000000000000000";
+
+const SPRING: &str = "306590037001600440288908881023002202000400020250000000000000020000000000,\
+    010231007022101440138808881023002200000300200000000000000000030000000000";
+
+const TWINKLE: &str = "010180007672209890022008880000040000000000000000000000000000000000000000";
+
+const YAWN: &str = "200002281133202440150008881023012801020000280400000000240000000000000000,\
+    005312391100300440091708880000012800000100000000000000000000000000000000,\
+    008220839113303440050508880000012800000100040000000000000000000000000000,\
+    004450645116401440038808880000012800000100040000000000000000000000000000";
+
+impl SoundExpression {
+    /// Returns the MakeCode sound expression of this sound, as played by
+    /// CODAL.
+    ///
+    /// ```
+    /// # use microbit_common as microbit;
+    /// use microbit::synth::{Sound, SoundExpression};
+    ///
+    /// let sound = Sound::parse(SoundExpression::Sad.makecode()).unwrap();
+    /// assert_eq!(sound.segments().count(), 2);
+    /// # for sound in [
+    /// #     SoundExpression::Giggle,
+    /// #     SoundExpression::Happy,
+    /// #     SoundExpression::Hello,
+    /// #     SoundExpression::Mysterious,
+    /// #     SoundExpression::Sad,
+    /// #     SoundExpression::Slide,
+    /// #     SoundExpression::Soaring,
+    /// #     SoundExpression::Spring,
+    /// #     SoundExpression::Twinkle,
+    /// #     SoundExpression::Yawn,
+    /// # ]
+    /// # .iter()
+    /// # {
+    /// #     assert!(Sound::parse(sound.makecode()).is_ok(), "{:?}", sound);
+    /// # }
+    /// ```
+    pub fn makecode(&self) -> &'static str {
+        match self {
+            SoundExpression::Giggle => GIGGLE,
+            SoundExpression::Happy => HAPPY,
+            SoundExpression::Hello => HELLO,
+            SoundExpression::Mysterious => MYSTERIOUS,
+            SoundExpression::Sad => SAD,
+            SoundExpression::Slide => SLIDE,
+            SoundExpression::Soaring => SOARING,
+            SoundExpression::Spring => SPRING,
+            SoundExpression::Twinkle => TWINKLE,
+            SoundExpression::Yawn => YAWN,
+        }
+    }
+
+    /// Returns this sound, parsed from its [`makecode`](Self::makecode)
+    /// expression.
+    pub fn sound(&self) -> Sound<'static> {
+        // the built-in expressions are all valid
+        Sound::parse(self.makecode()).unwrap_or(Sound { makecode: "" })
+    }
+
+    /// Returns the segments of this sound.
+    pub fn segments(&self) -> impl Iterator<Item = Segment> {
+        self.sound().segments()
+    }
+
+    /// Render this sound at `sample_rate_hz`.
+    pub fn samples(&self, sample_rate_hz: u32) -> Samples<impl Iterator<Item = Segment>> {
+        self.sound().samples(sample_rate_hz)
+    }
+
+    /// Returns the duration of this sound, in milliseconds.
+    pub fn duration_ms(&self) -> u32 {
+        self.segments()
+            .map(|segment| u32::from(segment.duration_ms))
+            .sum()
+    }
+}
+
+impl fmt::Display for Segment {
+    /// Formats the segment in the MakeCode format.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let encoded = self.encode();
+        // the encoding only contains ASCII digits
+        f.write_str(str::from_utf8(&encoded).map_err(|_| fmt::Error)?)
+    }
+}
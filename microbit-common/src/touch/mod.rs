@@ -0,0 +1,456 @@
+//! Touch sensing on the edge connector pads and the micro:bit V2 logo.
+//!
+//! The big edge connector pads `P0`, `P1` and `P2` (and the gold logo on the
+//! micro:bit V2) can all be used as touch inputs. This module provides
+//! [`TouchPin`], which takes any of them and reports whether it is being
+//! touched, along with the [`TouchEvent`]s derived from that.
+//!
+//! # Modes
+//!
+//! Each pad has a 10 MΩ pull-up on the board, and a [`TouchPin`] can sense a
+//! touch in one of two ways, selected with [`TouchMode`]:
+//!
+//! - [`TouchMode::Resistive`] is how the micro:bit V1 detects touch. You hold
+//!   the `GND` pad with one hand and touch the pin with the other; the
+//!   resistance of your body pulls the pin low against the pull-up, so the pin
+//!   simply reads low while touched.
+//! - [`TouchMode::Capacitive`] is how the micro:bit V2 logo detects touch, and
+//!   needs no `GND` contact. The pin is driven low briefly to drain any charge
+//!   from the pad, then switched to a floating input, and the code counts how
+//!   many microseconds elapse before the pull-up charges the pad back to a
+//!   logic high. A finger adds capacitance, so an untouched pad charges in
+//!   roughly 15 µs while a touched pad takes substantially longer.
+//!
+//! In both modes a measurement produces a raw value (see
+//! [`TouchPin::read_raw`]), which is compared against a threshold with
+//! hysteresis to avoid flicker near the boundary.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # use microbit_common as microbit;
+//! use microbit::Board;
+//! use microbit::hal::Timer;
+//! use microbit::touch::{TouchMode, TouchPin};
+//!
+//! let board = Board::take().unwrap();
+//! let mut timer = Timer::new(board.TIMER0);
+//! let mut pad0 = TouchPin::new(board.edge.e00, TouchMode::Resistive);
+//! let mut pad1 = TouchPin::new(board.edge.e01, TouchMode::Capacitive);
+//!
+//! loop {
+//!     if pad0.is_touched(&mut timer) || pad1.is_touched(&mut timer) {
+//!         // one of the pads is being touched
+//!     }
+//! }
+//! ```
+//!
+//! # Events
+//!
+//! Instead of comparing successive [`TouchPin::is_touched`] results yourself,
+//! [`TouchPin::poll`] reports [`TouchEvent`]s in the same way the micro:bit
+//! runtime reports button events: [`Touched`](TouchEvent::Touched) and
+//! [`Released`](TouchEvent::Released) for every edge,
+//! [`Tap`](TouchEvent::Tap) for a short touch and
+//! [`LongTouch`](TouchEvent::LongTouch) once a touch has been held long enough.
+//!
+//! The event logic lives in [`TouchTracker`], which works purely on raw
+//! measurements and elapsed milliseconds, so it can be driven from a scripted
+//! sequence of charge times:
+//!
+//! ```
+//! # use microbit_common as microbit;
+//! use microbit::touch::{TouchEvent, TouchTracker};
+//!
+//! let mut tracker = TouchTracker::new();
+//! let mut events = Vec::new();
+//! // Raw charge times in µs, one measurement every 50 ms.
+//! for charge_time in [15, 16, 80, 85, 82, 20, 15] {
+//!     events.extend(tracker.update(charge_time, 50));
+//! }
+//! assert_eq!(
+//!     events,
+//!     [TouchEvent::Touched, TouchEvent::Released, TouchEvent::Tap]
+//! );
+//! ```
+//!
+//! # Scanning many pads
+//!
+//! [`TouchPin`] measures on demand and blocks while doing so. To watch several
+//! pads at once, for example for a touch keyboard, use a
+//! [`TouchScanner`](scanner::TouchScanner), which measures them one after the
+//! other from a timer interrupt.
+//!
+//! # Attribution
+//!
+//! The charge-timing approach used here is adapted from the MIT-licensed
+//! `pdx-cs-rust-embedded/mb2-touch` demonstration by the Portland State
+//! University CS Rust Embedded group.
+
+use crate::gpio::{EDGE00, EDGE01, EDGE02};
+use crate::hal::gpio::{Floating, Input, Level, Pin};
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::InputPin;
+
+pub mod scanner;
+
+/// Microseconds the pad is driven low to drain it before each measurement.
+const RESET_TIME_US: u32 = 10;
+
+/// Ceiling on the raw measurement, in microseconds. The measurement loop
+/// stops here so a stuck-low pin can never hang the caller.
+pub const MAX_TICKS: u32 = 5_000;
+
+/// Raw value (µs) above which an untouched pad becomes "touched".
+const TOUCH_HIGH: u32 = 50;
+
+/// Raw value (µs) below which a touched pad becomes "released". Keeping this
+/// lower than [`TOUCH_HIGH`] gives hysteresis, which prevents the reported
+/// state from flickering when the charge time hovers near the boundary.
+const TOUCH_LOW: u32 = 30;
+
+/// Default longest touch, in milliseconds, that is still reported as a
+/// [`TouchEvent::Tap`] when released.
+pub const DEFAULT_TAP_MAX_MS: u32 = 500;
+
+/// Default time, in milliseconds, a touch has to be held before
+/// [`TouchEvent::LongTouch`] is reported. This matches the long click time
+/// used for the buttons by the micro:bit runtime.
+pub const DEFAULT_LONG_TOUCH_MS: u32 = 1_000;
+
+/// An event reported by [`TouchPin::poll`] or [`TouchTracker::update`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TouchEvent {
+    /// The pad has started being touched.
+    Touched,
+    /// The pad is no longer being touched.
+    Released,
+    /// The pad was released after a touch no longer than the tap threshold.
+    ///
+    /// Reported right after the corresponding [`Released`](TouchEvent::Released).
+    Tap,
+    /// The pad has been held for the long touch threshold.
+    ///
+    /// Reported once per touch, while the pad is still being touched.
+    LongTouch,
+}
+
+/// The events produced by a single [`TouchTracker::update`].
+///
+/// A single measurement can produce at most two events (a
+/// [`Released`](TouchEvent::Released) followed by a [`Tap`](TouchEvent::Tap)),
+/// which are yielded in the order they happened.
+#[derive(Copy, Clone, Debug)]
+pub struct TouchEvents {
+    events: [Option<TouchEvent>; 2],
+    next: usize,
+}
+
+impl TouchEvents {
+    const fn none() -> Self {
+        Self {
+            events: [None, None],
+            next: 0,
+        }
+    }
+
+    fn push(&mut self, event: TouchEvent) {
+        if let Some(slot) = self.events.iter_mut().find(|slot| slot.is_none()) {
+            *slot = Some(event);
+        }
+    }
+}
+
+impl Iterator for TouchEvents {
+    type Item = TouchEvent;
+
+    fn next(&mut self) -> Option<TouchEvent> {
+        let event = self.events.get(self.next).copied().flatten();
+        if event.is_some() {
+            self.next += 1;
+        }
+        event
+    }
+}
+
+/// Turns raw charge time measurements into a debounced touch state and
+/// [`TouchEvent`]s.
+///
+/// This holds no hardware, so it can be fed with recorded or scripted
+/// measurements; see the [module documentation](self) for an example.
+#[derive(Copy, Clone, Debug)]
+pub struct TouchTracker {
+    touched: bool,
+    held_ms: u32,
+    long_reported: bool,
+    tap_max_ms: u32,
+    long_touch_ms: u32,
+}
+
+impl TouchTracker {
+    /// Create a tracker using [`DEFAULT_TAP_MAX_MS`] and
+    /// [`DEFAULT_LONG_TOUCH_MS`].
+    pub const fn new() -> Self {
+        Self::with_timing(DEFAULT_TAP_MAX_MS, DEFAULT_LONG_TOUCH_MS)
+    }
+
+    /// Create a tracker with custom timing thresholds, in milliseconds.
+    ///
+    /// Touches released after at most `tap_max_ms` are reported as a
+    /// [`TouchEvent::Tap`]; touches held for `long_touch_ms` are reported as a
+    /// [`TouchEvent::LongTouch`].
+    pub const fn with_timing(tap_max_ms: u32, long_touch_ms: u32) -> Self {
+        Self {
+            touched: false,
+            held_ms: 0,
+            long_reported: false,
+            tap_max_ms,
+            long_touch_ms,
+        }
+    }
+
+    /// Returns `true` if the last measurement was debounced as a touch.
+    pub fn is_touched(&self) -> bool {
+        self.touched
+    }
+
+    /// Returns how long, in milliseconds, the current touch has been held.
+    ///
+    /// This is `0` while the pad is not being touched.
+    pub fn held_ms(&self) -> u32 {
+        if self.touched {
+            self.held_ms
+        } else {
+            0
+        }
+    }
+
+    /// Feed one raw charge time measurement (in microseconds) taken
+    /// `elapsed_ms` milliseconds after the previous one, and return the
+    /// resulting events.
+    ///
+    /// The charge time is debounced with the same hysteresis as
+    /// [`TouchPin::is_touched`].
+    pub fn update(&mut self, charge_time: u32, elapsed_ms: u32) -> TouchEvents {
+        let mut events = TouchEvents::none();
+        let touched = if self.touched {
+            charge_time > TOUCH_LOW
+        } else {
+            charge_time > TOUCH_HIGH
+        };
+
+        match (self.touched, touched) {
+            (false, true) => {
+                self.held_ms = 0;
+                self.long_reported = false;
+                events.push(TouchEvent::Touched);
+            }
+            (true, true) => {
+                self.held_ms = self.held_ms.saturating_add(elapsed_ms);
+                if !self.long_reported && self.held_ms >= self.long_touch_ms {
+                    self.long_reported = true;
+                    events.push(TouchEvent::LongTouch);
+                }
+            }
+            (true, false) => {
+                events.push(TouchEvent::Released);
+                if self.held_ms <= self.tap_max_ms {
+                    events.push(TouchEvent::Tap);
+                }
+            }
+            (false, false) => {}
+        }
+
+        self.touched = touched;
+        events
+    }
+}
+
+impl Default for TouchTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// How a [`TouchPin`] senses a touch.
+///
+/// See the [module documentation](self) for details.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TouchMode {
+    /// Touching the pad and `GND` at the same time pulls the pin low.
+    Resistive,
+    /// A finger on the pad slows down charging it through the pull-up.
+    Capacitive,
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// A pin that is wired up as a touch pad on the board.
+///
+/// This is implemented for the `EDGE00`, `EDGE01` and `EDGE02` pins of
+/// [`board::Edge`](crate::board::Edge) and, on the micro:bit V2, for the logo
+/// pin `P1_04`, in any mode.
+pub trait TouchPad: sealed::Sealed {
+    /// Configure the pin as a floating input and degrade it.
+    fn into_touch_pad(self) -> Pin<Input<Floating>>;
+}
+
+macro_rules! touch_pads {
+    ($($pad:ident),+) => {
+        $(
+            impl<MODE> sealed::Sealed for $pad<MODE> {}
+
+            impl<MODE> TouchPad for $pad<MODE> {
+                fn into_touch_pad(self) -> Pin<Input<Floating>> {
+                    self.into_floating_input().degrade()
+                }
+            }
+        )+
+    };
+}
+
+touch_pads!(EDGE00, EDGE01, EDGE02);
+
+#[cfg(feature = "v2")]
+use crate::hal::gpio::p1::P1_04;
+
+#[cfg(feature = "v2")]
+touch_pads!(P1_04);
+
+/// A touch sensitive pad.
+///
+/// Construct one from a [`TouchPad`] with [`TouchPin::new`], then poll it
+/// with [`TouchPin::is_touched`] or [`TouchPin::poll`]. See the [module
+/// documentation](self) for details on how detection works.
+pub struct TouchPin {
+    // Held as an `Option` so the pin can be temporarily moved out to flip its
+    // direction (output to charge, input to measure) on each measurement.
+    pin: Option<Pin<Input<Floating>>>,
+    mode: TouchMode,
+    tracker: TouchTracker,
+}
+
+impl TouchPin {
+    /// Create a [`TouchPin`] from a touch pad, sensing in the given mode.
+    pub fn new<P: TouchPad>(pad: P, mode: TouchMode) -> Self {
+        Self::with_tracker(pad, mode, TouchTracker::new())
+    }
+
+    /// Create a [`TouchPin`] with custom [`TouchEvent`] timing thresholds.
+    ///
+    /// See [`TouchTracker::with_timing`] for the meaning of the thresholds.
+    pub fn with_timing<P: TouchPad>(
+        pad: P,
+        mode: TouchMode,
+        tap_max_ms: u32,
+        long_touch_ms: u32,
+    ) -> Self {
+        Self::with_tracker(
+            pad,
+            mode,
+            TouchTracker::with_timing(tap_max_ms, long_touch_ms),
+        )
+    }
+
+    fn with_tracker<P: TouchPad>(pad: P, mode: TouchMode, tracker: TouchTracker) -> Self {
+        Self {
+            pin: Some(pad.into_touch_pad()),
+            mode,
+            tracker,
+        }
+    }
+
+    /// Returns the mode this pin senses touch with.
+    pub fn mode(&self) -> TouchMode {
+        self.mode
+    }
+
+    /// Change the mode this pin senses touch with.
+    pub fn set_mode(&mut self, mode: TouchMode) {
+        self.mode = mode;
+    }
+
+    /// Returns `true` while the pad is being touched.
+    ///
+    /// This performs one measurement, which may drive the pin and use `delay`
+    /// for the required microsecond timing. The result is debounced with
+    /// hysteresis, so a finger resting on the pad reports `true` consistently
+    /// and a released pad reports `false` consistently.
+    pub fn is_touched<D: DelayNs>(&mut self, delay: &mut D) -> bool {
+        let raw = self.read_raw(delay);
+        self.tracker.update(raw, 0);
+        self.tracker.is_touched()
+    }
+
+    /// Perform one measurement and return the resulting [`TouchEvent`]s.
+    ///
+    /// `elapsed_ms` is the time since the previous call, which is used to tell
+    /// taps from long touches. Call this regularly (every 10 to 50 ms works
+    /// well) and avoid mixing it with [`TouchPin::is_touched`], which does not
+    /// account for elapsed time.
+    pub fn poll<D: DelayNs>(&mut self, delay: &mut D, elapsed_ms: u32) -> TouchEvents {
+        let raw = self.read_raw(delay);
+        self.tracker.update(raw, elapsed_ms)
+    }
+
+    /// Perform one raw measurement without updating the touch state.
+    ///
+    /// In [`TouchMode::Capacitive`] this is the time in microseconds for the
+    /// pad to charge to a logic high, capped at [`MAX_TICKS`]. In
+    /// [`TouchMode::Resistive`] it is [`MAX_TICKS`] while the pin reads low and
+    /// `0` otherwise. In both cases a larger value means a touch.
+    pub fn read_raw<D: DelayNs>(&mut self, delay: &mut D) -> u32 {
+        // The pin is moved out of `self.pin` to flip its direction and is
+        // always stored back before returning, so `self.pin` is `Some` between
+        // calls. If the pin were somehow absent, report `0` (untouched), which
+        // is the safe default.
+        let Some(pin) = self.pin.take() else {
+            return 0;
+        };
+        let (pin, raw) = match self.mode {
+            TouchMode::Resistive => resistive_level(pin),
+            TouchMode::Capacitive => charge_time(pin, delay),
+        };
+        self.pin = Some(pin);
+        raw
+    }
+
+    /// Release the pin, left configured as a floating input.
+    pub fn free(mut self) -> Pin<Input<Floating>> {
+        // `pin` is only ever `None` in the middle of `read_raw`.
+        self.pin.take().unwrap()
+    }
+}
+
+/// Read the level of a resistive touch pad: [`MAX_TICKS`] while pulled low
+/// by a touch, `0` otherwise.
+pub(crate) fn resistive_level(mut pin: Pin<Input<Floating>>) -> (Pin<Input<Floating>>, u32) {
+    let raw = if pin.is_low().unwrap_or(false) {
+        MAX_TICKS
+    } else {
+        0
+    };
+    (pin, raw)
+}
+
+/// Drain a capacitive touch pad, then return the time in microseconds for it
+/// to charge back to a logic high (capped at [`MAX_TICKS`]).
+pub(crate) fn charge_time<D: DelayNs>(
+    pin: Pin<Input<Floating>>,
+    delay: &mut D,
+) -> (Pin<Input<Floating>>, u32) {
+    // Drive low to drain the pad to 0 V.
+    let out = pin.into_push_pull_output(Level::Low);
+    delay.delay_us(RESET_TIME_US);
+
+    // Switch to a floating input and time the charge back up to high.
+    let mut input = out.into_floating_input();
+    let mut count = 0u32;
+    while count < MAX_TICKS && input.is_low().unwrap_or(false) {
+        delay.delay_us(1);
+        count += 1;
+    }
+    (input, count)
+}
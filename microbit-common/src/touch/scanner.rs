@@ -0,0 +1,232 @@
+//! Background scanning of several touch pads with a single timer.
+
+use super::{charge_time, resistive_level, TouchMode, TouchTracker};
+use crate::hal::{
+    gpio::{Floating, Input, Pin},
+    timer::Instance,
+};
+use embedded_hal::delay::DelayNs;
+
+/// Default time between the end of a pad measurement and the start of the
+/// next one, in microseconds.
+pub const DEFAULT_INTERVAL_US: u16 = 2_000;
+
+/// Shortest interval, leaving the interrupt handler time to return before
+/// the next scan is due.
+const MIN_INTERVAL_US: u16 = 100;
+
+/// Scans several touch pads round-robin from a timer interrupt.
+///
+/// The scanner owns the pads and a TIMER peripheral. Every
+/// [`interval`](TouchScanner::set_interval_us) the timer fires an interrupt
+/// and [`handle_timer_event`](TouchScanner::handle_timer_event), called from
+/// that interrupt, measures the next pad in turn. The latest results are
+/// available at any time through [`touched`](TouchScanner::touched) and
+/// [`raw`](TouchScanner::raw), so a whole touch keyboard costs one timer and
+/// one interrupt instead of a blocking measurement per pad.
+///
+/// The timer is set to 16-bit mode with a 1 MHz clock (1 µs ticks). It uses
+/// CC0 to schedule the scans and CC1 to time the measurements.
+///
+/// Up to 32 pads are supported, one bit each in [`touched`](TouchScanner::touched).
+///
+/// # Example
+///
+/// ```no_run
+/// # use microbit_common as microbit;
+/// use microbit::{
+///     touch::{scanner::TouchScanner, TouchMode},
+///     Board,
+/// };
+///
+/// let board = Board::take().unwrap();
+/// let pads = [
+///     board.edge.e00.into_floating_input().degrade(),
+///     board.edge.e01.into_floating_input().degrade(),
+///     board.edge.e02.into_floating_input().degrade(),
+/// ];
+/// let mut scanner = TouchScanner::new(board.TIMER1, pads, TouchMode::Capacitive);
+///
+/// // in the TIMER1 interrupt
+/// scanner.handle_timer_event();
+///
+/// // anywhere else
+/// if scanner.touched() & 0b010 != 0 {
+///     // pad 1 is being touched
+/// }
+/// ```
+///
+/// As with the non-blocking [`Display`](crate::display::nonblocking::Display),
+/// the scanner will usually live in a `Mutex<RefCell<Option<...>>>` shared
+/// between the interrupt handler and the rest of the program.
+pub struct TouchScanner<T: Instance, const N: usize> {
+    timer: T,
+    // Held as `Option`s so a pad can be temporarily moved out to flip its
+    // direction while it is measured.
+    pads: [Option<Pin<Input<Floating>>>; N],
+    mode: TouchMode,
+    trackers: [TouchTracker; N],
+    raw: [u32; N],
+    next: usize,
+    interval_us: u16,
+}
+
+impl<T: Instance, const N: usize> TouchScanner<T, N> {
+    /// Create a scanner for `pads`, sensing in the given mode, and start the
+    /// timer.
+    ///
+    /// The pads are expected to have a pull-up, as the edge connector pads
+    /// and the micro:bit V2 logo do.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are more than 32 pads.
+    pub fn new(timer: T, pads: [Pin<Input<Floating>>; N], mode: TouchMode) -> Self {
+        assert!(N <= 32, "a TouchScanner supports at most 32 pads");
+        let mut scanner = Self {
+            timer,
+            pads: pads.map(Some),
+            mode,
+            trackers: [TouchTracker::new(); N],
+            raw: [0; N],
+            next: 0,
+            interval_us: DEFAULT_INTERVAL_US,
+        };
+        scanner.start();
+        scanner
+    }
+
+    /// Stop the timer and give back the timer and the pads.
+    pub fn free(self) -> (T, [Pin<Input<Floating>>; N]) {
+        let timer0 = self.timer.as_timer0();
+        timer0.tasks_stop.write(|w| unsafe { w.bits(1) });
+        timer0.intenclr.write(|w| w.compare0().set_bit());
+        // A pad is only ever `None` in the middle of a measurement.
+        (self.timer, self.pads.map(Option::unwrap))
+    }
+
+    /// Set the time between the end of a pad measurement and the start of
+    /// the next one, in microseconds.
+    ///
+    /// The next scan is scheduled once the measurement is over, so a
+    /// capacitive measurement, which takes up to
+    /// [`MAX_TICKS`](super::MAX_TICKS) microseconds, can take longer than
+    /// the interval. Every pad is measured once every `N * interval_us`, plus
+    /// the time taken by the measurements.
+    ///
+    /// Intervals below 100µs are raised to 100µs.
+    pub fn set_interval_us(&mut self, interval_us: u16) {
+        self.interval_us = interval_us.max(MIN_INTERVAL_US);
+    }
+
+    /// Returns a bitmask of the pads currently being touched.
+    ///
+    /// Bit `i` is set while `pads[i]` is being touched.
+    pub fn touched(&self) -> u32 {
+        self.trackers
+            .iter()
+            .enumerate()
+            .filter(|(_, tracker)| tracker.is_touched())
+            .fold(0, |mask, (i, _)| mask | 1 << i)
+    }
+
+    /// Returns `true` while the pad at `index` is being touched.
+    pub fn is_touched(&self, index: usize) -> bool {
+        self.trackers
+            .get(index)
+            .is_some_and(TouchTracker::is_touched)
+    }
+
+    /// Returns the latest raw measurement of the pad at `index`.
+    ///
+    /// See [`TouchPin::read_raw`](super::TouchPin::read_raw) for the meaning
+    /// of the value.
+    pub fn raw(&self, index: usize) -> u32 {
+        self.raw.get(index).copied().unwrap_or(0)
+    }
+
+    /// Returns the latest raw measurements of all pads.
+    pub fn raw_values(&self) -> &[u32; N] {
+        &self.raw
+    }
+
+    /// Measure the next pad and schedule the following scan.
+    ///
+    /// Call this in an interrupt handler for the timer you're using. This
+    /// method takes care of clearing the timer's event registers.
+    pub fn handle_timer_event(&mut self) {
+        let timer0 = self.timer.as_timer0();
+        let reg = &timer0.events_compare[0];
+        if reg.read().bits() == 0 {
+            return;
+        }
+        reg.reset();
+
+        if N > 0 {
+            let index = self.next;
+            self.next = (index + 1) % N;
+            if let Some(pad) = self.pads[index].take() {
+                let (pad, raw) = match self.mode {
+                    TouchMode::Resistive => resistive_level(pad),
+                    TouchMode::Capacitive => charge_time(pad, &mut ScanClock(&self.timer)),
+                };
+                self.pads[index] = Some(pad);
+                self.raw[index] = raw;
+                self.trackers[index].update(raw, 0);
+            }
+        }
+
+        // schedule from the end of the measurement, which can outlast the
+        // interval: a compare value already passed would only match again
+        // once the counter wraps around
+        let now = ScanClock(&self.timer).now();
+        let next_scan = (u32::from(now) + u32::from(self.interval_us)) & 0xFFFF;
+        timer0.cc[0].write(|w| unsafe { w.bits(next_scan) });
+    }
+
+    fn start(&mut self) {
+        let timer0 = self.timer.as_timer0();
+        // stop and reset timer
+        timer0.tasks_stop.write(|w| unsafe { w.bits(1) });
+        timer0.tasks_clear.write(|w| unsafe { w.bits(1) });
+
+        timer0.mode.write(|w| w.mode().timer());
+        // set as 16 bits
+        timer0.bitmode.write(|w| w.bitmode()._16bit());
+        // set frequency to 1MHz
+        timer0.prescaler.write(|w| unsafe { w.bits(4) });
+        // the counter runs freely, scans are scheduled by moving CC0 along
+        timer0.shorts.write(|w| unsafe { w.bits(0) });
+        timer0.cc[0].write(|w| unsafe { w.bits(self.interval_us.into()) });
+
+        // enable compare interrupt
+        timer0.intenset.write(|w| w.compare0().set());
+
+        // start
+        timer0.tasks_start.write(|w| unsafe { w.bits(1) });
+    }
+}
+
+/// Busy-wait delays based on the scanner's free running timer, so the charge
+/// time measurements don't need a timer of their own.
+struct ScanClock<'a, T: Instance>(&'a T);
+
+impl<T: Instance> ScanClock<'_, T> {
+    fn now(&self) -> u16 {
+        let timer0 = self.0.as_timer0();
+        timer0.tasks_capture[1].write(|w| unsafe { w.bits(1) });
+        timer0.cc[1].read().bits() as u16
+    }
+}
+
+impl<T: Instance> DelayNs for ScanClock<'_, T> {
+    fn delay_ns(&mut self, ns: u32) {
+        let mut remaining = ns.div_ceil(1_000);
+        while remaining > 0 {
+            let ticks = remaining.min(u16::MAX.into());
+            let start = self.now();
+            while u32::from(self.now().wrapping_sub(start)) < ticks {}
+            remaining -= ticks;
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use super::gpio::{
-    DisplayPins, BTN_A, BTN_B, EDGE00, EDGE01, EDGE02, EDGE08, EDGE12, EDGE16, SCL, SDA, UART_RX,
-    UART_TX,
+    DisplayPins, BTN_A, BTN_B, EDGE00, EDGE01, EDGE02, EDGE08, EDGE12, EDGE16, INT_ACC, SCL, SDA,
+    UART_RX, UART_TX,
 };
 use crate::{
     hal::{
@@ -28,6 +28,9 @@ pub struct Board {
     /// I2C shared internal and external bus pins
     pub i2c: I2CPins,
 
+    /// motion sensor interrupt pin
+    pub int_acc: INT_ACC,
+
     /// UART to debugger pins
     pub uart: UartPins,
 
@@ -89,7 +92,8 @@ pub struct Board {
     pub RTC0: pac::RTC0,
 
     /// nRF51 peripheral: TEMP <br>
-    /// Can be used with [`Temp::new()`](`crate::hal::temp::Temp::new()`)
+    /// Can be used with [`Temperature::new()`](crate::sensors::Temperature::new())
+    /// or [`Temp::new()`](`crate::hal::temp::Temp::new()`)
     pub TEMP: pac::TEMP,
 
     /// nRF51 peripheral: TIMER0
@@ -189,7 +193,6 @@ impl Board {
                 p0_22: p0parts.p0_22,
                 p0_23: p0parts.p0_23,
                 p0_27: p0parts.p0_27,
-                p0_28: p0parts.p0_28,
                 p0_29: p0parts.p0_29,
             },
             edge: Edge {
@@ -222,6 +225,7 @@ impl Board {
                 scl: p0parts.p0_00.into_floating_input(),
                 sda: p0parts.p0_30.into_floating_input(),
             },
+            int_acc: p0parts.p0_28.into_pullup_input(),
             uart: UartPins {
                 tx: p0parts.p0_24.into_push_pull_output(Level::Low),
                 rx: p0parts.p0_25.into_floating_input(),
@@ -305,7 +309,7 @@ pub struct Pins {
     // pub p0_25: p0::P0_25<Disconnected>, // UART RX
     // pub p0_26: p0::P0_26<Disconnected>, // BTN_B
     pub p0_27: p0::P0_27<Disconnected>,
-    pub p0_28: p0::P0_28<Disconnected>,
+    // pub p0_28: p0::P0_28<Disconnected>, // INT_ACC
     pub p0_29: p0::P0_29<Disconnected>,
     // pub p0_30: p0::P0_30<Disconnected>, // SDA
 }
@@ -5,7 +5,7 @@
 //! Where appropriate the pins are restricted with the appropriate `MODE`
 //! from `nrf-hal`.
 #![allow(clippy::upper_case_acronyms, missing_docs)]
-use crate::hal::gpio::{p0, Floating, Input, Output, Pin, PushPull};
+use crate::hal::gpio::{p0, Floating, Input, Output, Pin, PullUp, PushPull};
 
 /* GPIO pads */
 pub type PAD0<MODE> = p0::P0_03<MODE>;
@@ -131,6 +131,9 @@ pub type SCK<MODE> = p0::P0_23<MODE>;
 pub type SCL = p0::P0_00<Input<Floating>>;
 pub type SDA = p0::P0_30<Input<Floating>>;
 
+/* motion sensor interrupt, active low */
+pub type INT_ACC = p0::P0_28<Input<PullUp>>;
+
 /* uart */
 pub type UART_TX = p0::P0_24<Output<PushPull>>;
 pub type UART_RX = p0::P0_25<Input<Floating>>;
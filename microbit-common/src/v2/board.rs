@@ -1,6 +1,6 @@
 use super::gpio::{
     DisplayPins, MicrophonePins, BTN_A, BTN_B, EDGE00, EDGE01, EDGE02, EDGE08, EDGE09, EDGE12,
-    EDGE16, INT_SCL, INT_SDA, SCL, SDA, UART_RX, UART_TX,
+    EDGE16, INT_ACC, INT_SCL, INT_SDA, SCL, SDA, UART_RX, UART_TX,
 };
 use crate::{
     hal::{
@@ -37,6 +37,9 @@ pub struct Board {
     /// I2C external bus pins
     pub i2c_external: I2CExternalPins,
 
+    /// motion sensor interrupt pin
+    pub int_acc: INT_ACC,
+
     /// UART to debugger pins
     pub uart: UartPins,
 
@@ -128,7 +131,8 @@ pub struct Board {
     pub SPIM3: pac::SPIM3,
 
     /// nRF52 peripheral: TEMP <br>
-    /// Can be used with [`Temp::new()`](`crate::hal::temp::Temp::new()`)
+    /// Can be used with [`Temperature::new()`](crate::sensors::Temperature::new())
+    /// or [`Temp::new()`](`crate::hal::temp::Temp::new()`)
     pub TEMP: pac::TEMP,
 
     /// nRF52 peripheral: TIMER0
@@ -247,7 +251,6 @@ impl Board {
                 p0_13: p0parts.p0_13,
                 p0_17: p0parts.p0_17,
                 p0_18: p0parts.p0_18,
-                p0_25: p0parts.p0_25,
                 p0_27: p0parts.p0_27,
                 p0_29: p0parts.p0_29,
                 p1_01: p1parts.p1_01,
@@ -298,6 +301,7 @@ impl Board {
                 scl: p0parts.p0_26.into_floating_input(),
                 sda: p1parts.p1_00.into_floating_input(),
             },
+            int_acc: p0parts.p0_25.into_pullup_input(),
             uart: UartPins {
                 tx: p0parts.p0_06.into_push_pull_output(Level::High),
                 rx: p1parts.p1_08.into_floating_input(),
@@ -394,7 +398,7 @@ pub struct Pins {
     // pub p0_22: p0::P0_22<Disconnected>, // LEDs
     // pub p0_23: p0::P0_23<Disconnected>, // BTN_B
     // pub p0_24: p0::P0_24<Disconnected>, // LEDs
-    pub p0_25: p0::P0_25<Disconnected>,
+    // pub p0_25: p0::P0_25<Disconnected>, // INT_ACC
     // pub p0_26: p0::P0_26<Disconnected>, // SCL
     pub p0_27: p0::P0_27<Disconnected>,
     // pub p0_28: p0::P0_28<Disconnected>, // LEDs
@@ -1,5 +1,5 @@
 #![allow(clippy::upper_case_acronyms, missing_docs)]
-use nrf52833_hal::gpio::{p0, p1, Floating, Input, OpenDrain, Output, Pin, PushPull};
+use nrf52833_hal::gpio::{p0, p1, Floating, Input, OpenDrain, Output, Pin, PullUp, PushPull};
 
 /* GPIO pads */
 pub type PAD0<MODE> = p0::P0_02<MODE>;
@@ -120,6 +120,9 @@ pub type SCK<MODE> = p0::P0_17<MODE>;
 pub type INT_SCL = p0::P0_08<Input<Floating>>;
 pub type INT_SDA = p0::P0_16<Input<Floating>>;
 
+/* motion sensor interrupt, active low */
+pub type INT_ACC = p0::P0_25<Input<PullUp>>;
+
 /* i2c - external */
 pub type SCL = p0::P0_26<Input<Floating>>;
 pub type SDA = p1::P1_00<Input<Floating>>;